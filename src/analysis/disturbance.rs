@@ -0,0 +1,267 @@
+use chrono::NaiveDateTime;
+
+use crate::analysis::phasor::interpolate;
+use crate::analysis::{PhaseGroup, PhasorOptions, RmsWindow, Sequence, TimeSeries};
+use crate::error::ComtradeError;
+use crate::Comtrade;
+
+/// Which side of a level counts as disturbed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    Over(f64),
+    Under(f64),
+}
+
+impl Threshold {
+    fn is_exceeded(&self, value: f64) -> bool {
+        match *self {
+            Threshold::Over(level) => value > level,
+            Threshold::Under(level) => value < level,
+        }
+    }
+}
+
+/// Condition which marks part of a record as disturbed. Channels are 0-indexed
+/// positions in `analog_channels` and levels are in the units the channels were
+/// recorded in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisturbanceTrigger {
+    /// One cycle RMS crossing a level. The RMS lags the waveform, so disturbances
+    /// are detected up to a cycle late.
+    Rms {
+        channel: usize,
+        threshold: Threshold,
+    },
+
+    /// One cycle RMS changing by more than `rate` per second, measured over a
+    /// cycle.
+    RateOfChange { channel: usize, rate: f64 },
+
+    /// Magnitude of a sequence component of a group of phases crossing a level.
+    /// Phasors are calculated with the phasor options in [`DisturbanceOptions`].
+    Sequence {
+        group: PhaseGroup,
+        sequence: Sequence,
+        threshold: Threshold,
+    },
+
+    /// Instantaneous value differing from the value a cycle earlier by more than
+    /// `level`. Catches changes to the waveform straight away, whatever their
+    /// effect on the RMS.
+    WaveformDeviation { channel: usize, level: f64 },
+}
+
+impl DisturbanceTrigger {
+    /// Analog channels the trigger looks at.
+    pub fn channels(&self) -> Vec<usize> {
+        match self {
+            DisturbanceTrigger::Rms { channel, .. }
+            | DisturbanceTrigger::RateOfChange { channel, .. }
+            | DisturbanceTrigger::WaveformDeviation { channel, .. } => vec![*channel],
+            DisturbanceTrigger::Sequence { group, .. } => vec![group.a, group.b, group.c],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisturbanceOptions {
+    pub phasor: PhasorOptions,
+
+    /// Disturbances from the same trigger closer together than this many seconds
+    /// are joined into one.
+    pub merge_gap: f64,
+}
+
+impl Default for DisturbanceOptions {
+    fn default() -> Self {
+        DisturbanceOptions {
+            phasor: PhasorOptions::default(),
+            merge_gap: 0.02,
+        }
+    }
+}
+
+/// Period where a trigger condition was met.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disturbance {
+    /// Time of the first disturbed value, in seconds relative to the start of the
+    /// record.
+    pub start: f64,
+
+    /// Time of the first undisturbed value after the disturbance, or the last
+    /// value if it lasts until the end of the record.
+    pub end: f64,
+
+    pub trigger: DisturbanceTrigger,
+}
+
+/// Periods where a series of flags is set, joining periods less than `merge_gap`
+/// apart.
+fn intervals(flags: &TimeSeries<bool>, merge_gap: f64) -> Vec<(f64, f64)> {
+    let mut intervals: Vec<(f64, f64)> = Vec::new();
+    let mut start: Option<f64> = None;
+    for (n, (time, flag)) in flags.iter().enumerate() {
+        match (*flag, start) {
+            (true, None) => start = Some(time),
+            (false, Some(s)) => {
+                intervals.push((s, time));
+                start = None;
+            }
+            _ => {}
+        }
+        if n + 1 == flags.len() {
+            if let Some(s) = start {
+                intervals.push((s, time));
+            }
+        }
+    }
+
+    let mut merged: Vec<(f64, f64)> = Vec::new();
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start - last.1 < merge_gap => last.1 = end,
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+impl Comtrade {
+    /// Whether the trigger condition is met at each time it can be evaluated.
+    fn trigger_flags(
+        &self,
+        trigger: &DisturbanceTrigger,
+        options: &DisturbanceOptions,
+    ) -> Result<TimeSeries<bool>, ComtradeError> {
+        self.check_line_frequency()?;
+        let cycle = 1.0 / self.line_frequency;
+
+        match trigger {
+            DisturbanceTrigger::Rms { channel, threshold } => Ok(self
+                .rms(*channel, RmsWindow::Cycles(1.0))?
+                .map(|v| threshold.is_exceeded(*v))),
+            DisturbanceTrigger::RateOfChange { channel, rate } => {
+                let rms = self.rms(*channel, RmsWindow::Cycles(1.0))?;
+                let mut flags = TimeSeries::new();
+                for (time, value) in rms.iter() {
+                    if let Some(previous) = rms.value_at(time - cycle) {
+                        flags.push(time, ((value - previous) / cycle).abs() > *rate);
+                    }
+                }
+                Ok(flags)
+            }
+            DisturbanceTrigger::Sequence {
+                group,
+                sequence,
+                threshold,
+            } => Ok(self
+                .sequence_components(group, &options.phasor)?
+                .map(|c| threshold.is_exceeded(c.get(*sequence).magnitude()))),
+            DisturbanceTrigger::WaveformDeviation { channel, level } => {
+                let data = self.analog_data(*channel)?;
+                let mut flags = TimeSeries::new();
+                let first = match self.timestamps.first() {
+                    Some(first) => *first,
+                    None => return Ok(flags),
+                };
+                for (n, time) in self.timestamps.iter().enumerate() {
+                    if time - cycle >= first {
+                        let previous = interpolate(&self.timestamps, data, time - cycle);
+                        flags.push(*time, (data[n] - previous).abs() > *level);
+                    }
+                }
+                Ok(flags)
+            }
+        }
+    }
+
+    /// Find the periods where any of the triggers' conditions are met, sorted by
+    /// start time.
+    pub fn detect_disturbances(
+        &self,
+        triggers: &[DisturbanceTrigger],
+        options: &DisturbanceOptions,
+    ) -> Result<Vec<Disturbance>, ComtradeError> {
+        let mut disturbances = Vec::new();
+        for trigger in triggers {
+            let flags = self.trigger_flags(trigger, options)?;
+            disturbances.extend(intervals(&flags, options.merge_gap).into_iter().map(
+                |(start, end)| Disturbance {
+                    start,
+                    end,
+                    trigger: trigger.clone(),
+                },
+            ));
+        }
+        disturbances.sort_by(|a, b| a.start.total_cmp(&b.start));
+        Ok(disturbances)
+    }
+
+    /// Absolute time of the start of the earliest disturbance, for records whose
+    /// `trigger_time` is missing or wrong.
+    pub fn estimate_trigger_time(&self, disturbances: &[Disturbance]) -> Option<NaiveDateTime> {
+        disturbances
+            .iter()
+            .map(|d| d.start)
+            .min_by(f64::total_cmp)
+            .map(|start| self.absolute_time(start))
+    }
+
+    /// Split a long record into one record per event. Each event covers
+    /// overlapping disturbances plus `pre_trigger` seconds before and
+    /// `post_trigger` seconds after, and is triggered at the start of its first
+    /// disturbance. Events which would overlap are combined.
+    pub fn split_events(
+        &self,
+        disturbances: &[Disturbance],
+        pre_trigger: f64,
+        post_trigger: f64,
+    ) -> Result<Vec<Comtrade>, ComtradeError> {
+        let mut periods: Vec<(f64, f64, f64)> = disturbances
+            .iter()
+            .map(|d| (d.start - pre_trigger, d.end + post_trigger, d.start))
+            .collect();
+        periods.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut events: Vec<(f64, f64, f64)> = Vec::new();
+        for (start, end, trigger) in periods {
+            match events.last_mut() {
+                Some(last) if start <= last.1 => {
+                    last.1 = last.1.max(end);
+                    last.2 = last.2.min(trigger);
+                }
+                _ => events.push((start, end, trigger)),
+            }
+        }
+
+        events
+            .into_iter()
+            .map(|(start, end, trigger)| {
+                // Slices exclude their end, so nudge it past the last sample wanted.
+                let mut event = self.slice(start..end + 1e-9)?;
+                event.trigger_time = self.absolute_time(trigger);
+                Ok(event)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_are_found_and_merged() {
+        let flags = TimeSeries {
+            timestamps: (0..10).map(|n| n as f64).collect(),
+            values: vec![
+                false, true, true, false, true, false, false, false, true, true,
+            ],
+        };
+        assert_eq!(
+            intervals(&flags, 0.5),
+            vec![(1.0, 3.0), (4.0, 5.0), (8.0, 9.0)]
+        );
+        assert_eq!(intervals(&flags, 1.5), vec![(1.0, 5.0), (8.0, 9.0)]);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::analysis::{FaultLoop, Phasor, PhasorOptions, PowerGroup};
+use crate::error::ComtradeError;
+use crate::Comtrade;
+
+/// Impedances of a transmission line, in ohms for the whole line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineParameters {
+    pub positive_sequence: Phasor,
+    pub zero_sequence: Phasor,
+
+    /// Length of the line, in whatever unit fault distances should be reported in.
+    pub length: f64,
+}
+
+impl LineParameters {
+    /// Residual compensation factor `k0 = (Z0 - Z1) / 3 Z1`.
+    pub fn k0(&self) -> Phasor {
+        (self.zero_sequence - self.positive_sequence) / (self.positive_sequence * 3.0)
+    }
+}
+
+/// Single-ended fault location algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultLocationMethod {
+    /// Ratio of the measured loop reactance to the line reactance. Accurate for
+    /// bolted faults, but fault resistance combined with load flow makes the fault
+    /// look nearer or further away.
+    Reactance,
+
+    /// Takagi method, which uses the change in current from the pre-fault load to
+    /// cancel out the voltage across the fault resistance.
+    Takagi,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultLocationOptions {
+    pub phasor: PhasorOptions,
+
+    /// Pre-fault phasors are taken from the window ending this many cycles before
+    /// the trigger.
+    pub pre_fault_cycles: f64,
+
+    /// Fault phasors are taken from the window ending this many cycles after the
+    /// trigger, leaving time for the fault transients to settle.
+    pub fault_cycles: f64,
+}
+
+impl Default for FaultLocationOptions {
+    fn default() -> Self {
+        FaultLocationOptions {
+            phasor: PhasorOptions::default(),
+            pre_fault_cycles: 1.0,
+            fault_cycles: 2.0,
+        }
+    }
+}
+
+/// Estimated distance to a fault.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaultLocation {
+    pub fault_loop: FaultLoop,
+    pub method: FaultLocationMethod,
+
+    /// Distance from the recording end in the units of the line length.
+    pub distance: f64,
+
+    /// Distance as a fraction of the line length.
+    pub per_unit: f64,
+
+    /// Apparent impedance of the fault loop in ohms.
+    pub impedance: Phasor,
+}
+
+impl Comtrade {
+    /// Estimate the distance to a fault on a line from the voltages and currents
+    /// recorded at one end, using phasors before and after `trigger_time`.
+    ///
+    /// Voltages must be phase-to-neutral. The fault loop is usually found by
+    /// classifying the fault first. Returns `None` if the record doesn't have full
+    /// phasor windows at the pre-fault and fault times.
+    pub fn fault_location(
+        &self,
+        group: &PowerGroup,
+        fault_loop: FaultLoop,
+        line: &LineParameters,
+        method: FaultLocationMethod,
+        options: &FaultLocationOptions,
+    ) -> Result<Option<FaultLocation>, ComtradeError> {
+        self.check_line_frequency()?;
+        let cycle = 1.0 / self.line_frequency;
+        let trigger = self.trigger_offset();
+
+        let fault_time = trigger + options.fault_cycles * cycle;
+        let fault = match self.group_phasors_at(group, fault_time, &options.phasor)? {
+            Some(phasors) => phasors,
+            None => return Ok(None),
+        };
+        let (voltage, current) = fault_loop.quantities(fault.voltages, fault.currents, line.k0());
+        let z1 = line.positive_sequence;
+
+        let per_unit = match method {
+            FaultLocationMethod::Reactance => (voltage / current).im / z1.im,
+            FaultLocationMethod::Takagi => {
+                let pre_fault_time = trigger - options.pre_fault_cycles * cycle;
+                let pre_fault =
+                    match self.group_phasors_at(group, pre_fault_time, &options.phasor)? {
+                        Some(phasors) => phasors,
+                        None => return Ok(None),
+                    };
+                // Superimposed (fault-only) loop current.
+                let (_, fault_current) =
+                    fault_loop.quantities(fault.voltages, fault.currents, Phasor::ZERO);
+                let (_, pre_fault_current) =
+                    fault_loop.quantities(pre_fault.voltages, pre_fault.currents, Phasor::ZERO);
+                let superimposed = (fault_current - pre_fault_current).conj();
+
+                (voltage * superimposed).im / (z1 * current * superimposed).im
+            }
+        };
+
+        Ok(Some(FaultLocation {
+            fault_loop,
+            method,
+            distance: per_unit * line.length,
+            per_unit,
+            impedance: voltage / current,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn k0_from_sequence_impedances() {
+        let line = LineParameters {
+            positive_sequence: Phasor::new(1.0, 10.0),
+            zero_sequence: Phasor::new(4.0, 40.0),
+            length: 1.0,
+        };
+        let k0 = line.k0();
+        assert!((k0 - Phasor::new(1.0, 0.0)).magnitude() < 1e-12);
+    }
+}
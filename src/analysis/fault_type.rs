@@ -0,0 +1,466 @@
+use crate::analysis::phasor::interpolate;
+use crate::analysis::rms::SquareIntegral;
+use crate::analysis::{FaultLoop, Phasor, PhasorOptions, PowerGroup, SequenceComponents};
+use crate::error::ComtradeError;
+use crate::status::StatusRoles;
+use crate::Comtrade;
+
+/// Phases involved in a fault, and whether it involves ground.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultType {
+    AG,
+    BG,
+    CG,
+    AB,
+    BC,
+    CA,
+    ABG,
+    BCG,
+    CAG,
+    ABC,
+    NoFault,
+}
+
+impl FaultType {
+    fn from_phases(phases: [bool; 3], ground: bool) -> Self {
+        match (phases, ground) {
+            ([true, false, false], _) => FaultType::AG,
+            ([false, true, false], _) => FaultType::BG,
+            ([false, false, true], _) => FaultType::CG,
+            ([true, true, false], false) => FaultType::AB,
+            ([false, true, true], false) => FaultType::BC,
+            ([true, false, true], false) => FaultType::CA,
+            ([true, true, false], true) => FaultType::ABG,
+            ([false, true, true], true) => FaultType::BCG,
+            ([true, false, true], true) => FaultType::CAG,
+            ([true, true, true], _) => FaultType::ABC,
+            ([false, false, false], _) => FaultType::NoFault,
+        }
+    }
+
+    /// Whether phases A, B and C are involved.
+    pub fn phases(&self) -> [bool; 3] {
+        match self {
+            FaultType::AG => [true, false, false],
+            FaultType::BG => [false, true, false],
+            FaultType::CG => [false, false, true],
+            FaultType::AB | FaultType::ABG => [true, true, false],
+            FaultType::BC | FaultType::BCG => [false, true, true],
+            FaultType::CA | FaultType::CAG => [true, false, true],
+            FaultType::ABC => [true, true, true],
+            FaultType::NoFault => [false, false, false],
+        }
+    }
+
+    pub fn involves_ground(&self) -> bool {
+        matches!(
+            self,
+            FaultType::AG
+                | FaultType::BG
+                | FaultType::CG
+                | FaultType::ABG
+                | FaultType::BCG
+                | FaultType::CAG
+        )
+    }
+
+    /// Impedance loop which sees the fault, for locating it. Phase-to-phase loops
+    /// are used for two phase to ground faults, and the AB loop for three phase
+    /// faults.
+    pub fn fault_loop(&self) -> Option<FaultLoop> {
+        match self {
+            FaultType::AG => Some(FaultLoop::AG),
+            FaultType::BG => Some(FaultLoop::BG),
+            FaultType::CG => Some(FaultLoop::CG),
+            FaultType::AB | FaultType::ABG | FaultType::ABC => Some(FaultLoop::AB),
+            FaultType::BC | FaultType::BCG => Some(FaultLoop::BC),
+            FaultType::CA | FaultType::CAG => Some(FaultLoop::CA),
+            FaultType::NoFault => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultClassificationOptions {
+    pub phasor: PhasorOptions,
+
+    /// Pre-fault phasors are taken from the window ending this many cycles before
+    /// the trigger.
+    pub pre_fault_cycles: f64,
+
+    /// Fault phasors are taken from the window ending this many cycles after the
+    /// trigger.
+    pub fault_cycles: f64,
+
+    /// Smallest change in phase current, as a fraction of the largest pre-fault
+    /// phase current, which counts as a fault.
+    pub current_pickup: f64,
+
+    /// Smallest drop in phase voltage, as a fraction of the largest pre-fault
+    /// phase voltage, which counts as a fault.
+    pub voltage_pickup: f64,
+
+    /// A phase is involved in the fault if its change is at least this fraction
+    /// of the largest change in any phase.
+    pub phase_selection: f64,
+
+    /// A fault involves ground if the change in residual current is at least this
+    /// fraction of the largest change in phase current.
+    pub ground_selection: f64,
+
+    /// A fault without ground is phase to phase if the change in negative
+    /// sequence current is at least this fraction of the change in positive
+    /// sequence current, and three phase otherwise.
+    pub negative_sequence_selection: f64,
+
+    /// Cycle-to-cycle change in the instantaneous current of a faulted phase, as a
+    /// fraction of the peak fault current change, which marks fault inception and
+    /// clearing.
+    pub waveform_threshold: f64,
+
+    /// A jump in current only marks clearing if the RMS current over the cycle
+    /// after it is below this fraction of the fault current, so that the decaying
+    /// DC offset of a fault current isn't mistaken for the fault clearing.
+    pub clearing_level: f64,
+
+    /// Trip and breaker status channels, used to report when the protection
+    /// tripped and the breaker opened and to refine the clearing time. `None`
+    /// classifies from the analog channels alone.
+    pub roles: Option<StatusRoles>,
+}
+
+impl Default for FaultClassificationOptions {
+    fn default() -> Self {
+        FaultClassificationOptions {
+            phasor: PhasorOptions::default(),
+            pre_fault_cycles: 1.0,
+            fault_cycles: 2.0,
+            current_pickup: 0.5,
+            voltage_pickup: 0.1,
+            phase_selection: 0.5,
+            ground_selection: 0.1,
+            negative_sequence_selection: 0.5,
+            waveform_threshold: 0.2,
+            clearing_level: 0.5,
+            roles: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaultClassification {
+    pub fault_type: FaultType,
+
+    /// How clear cut the classification was, from 0 to 1. Each decision (is there
+    /// a fault, is each phase involved, is ground involved) scores by how far its
+    /// measurement was from the threshold, relative to the threshold, and the
+    /// lowest score is taken.
+    pub confidence: f64,
+
+    /// Time the fault started in seconds relative to the start of the record.
+    pub inception: Option<f64>,
+
+    /// Time the fault current stopped, relative to the start of the record.
+    /// `None` if the fault lasts until the end of the record.
+    pub clearing: Option<f64>,
+
+    /// Time the first trip signal asserted, relative to the start of the record.
+    /// Only found when status roles are given.
+    pub trip: Option<f64>,
+
+    /// Time the breaker first indicated open after the trip (or inception),
+    /// relative to the start of the record. Only found when status roles are
+    /// given.
+    pub breaker_open: Option<f64>,
+}
+
+/// Score a decision by how far `value` was from `threshold`, clamped to 0..=1.
+fn decision_confidence(value: f64, threshold: f64) -> f64 {
+    if threshold <= 0.0 {
+        return 1.0;
+    }
+    ((value - threshold).abs() / threshold).min(1.0)
+}
+
+/// Phases involved in a fault without ground, from the superimposed phase
+/// currents, with the confidence of the decision, or `None` without any change in
+/// current to go on. Three phase faults have little negative sequence current.
+/// For a phase to phase fault the healthy phase has no superimposed current, so
+/// taking it as the reference its positive and negative sequence currents are
+/// equal and opposite.
+fn sequence_phase_selection(
+    current_change: [Phasor; 3],
+    threshold: f64,
+) -> Option<([bool; 3], f64)> {
+    let [a, b, c] = current_change;
+    let components = SequenceComponents::from_phases(a, b, c);
+    let positive = components.positive.magnitude();
+    if positive == 0.0 {
+        return None;
+    }
+    let negative_ratio = components.negative.magnitude() / positive;
+    let confidence = decision_confidence(negative_ratio, threshold);
+    if negative_ratio < threshold {
+        return Some(([true; 3], confidence));
+    }
+
+    let mismatch = |healthy: usize| {
+        let rotated = [0, 1, 2].map(|n| current_change[(healthy + n) % 3]);
+        let components = SequenceComponents::from_phases(rotated[0], rotated[1], rotated[2]);
+        (components.positive + components.negative).magnitude() / components.positive.magnitude()
+    };
+    let healthy = (0..3)
+        .min_by(|x, y| mismatch(*x).total_cmp(&mismatch(*y)))
+        .unwrap();
+    let mut phases = [true; 3];
+    phases[healthy] = false;
+    Some((phases, confidence))
+}
+
+fn max_magnitude(phasors: impl Iterator<Item = Phasor>) -> f64 {
+    phasors.map(|p| p.magnitude()).fold(0.0, f64::max)
+}
+
+impl Comtrade {
+    /// Classify the fault seen by a circuit's phase-to-neutral voltages and
+    /// currents, by comparing phasors before and after `trigger_time`.
+    ///
+    /// Faulted phases are those whose superimposed (fault minus pre-fault) current
+    /// and voltage drop are large compared to the other phases, and ground is
+    /// involved if there is a significant superimposed residual current. Faults
+    /// without ground are then told apart by their superimposed sequence currents:
+    /// three phase faults have next to no negative sequence, and the healthy phase
+    /// of a phase to phase fault is the one whose positive and negative sequence
+    /// currents cancel out. Fault inception and clearing are found from jumps in
+    /// the cycle-to-cycle difference of the faulted phase currents either side of
+    /// the trigger, and the current has to stay down for a cycle after clearing.
+    ///
+    /// With status roles in the options, the first trip and breaker open
+    /// indications are reported, the clearing time is searched for from the trip
+    /// onwards, and the breaker opening stands in for the clearing time if the
+    /// currents don't show it.
+    ///
+    /// Records without full phasor windows around the trigger are classified as
+    /// [`FaultType::NoFault`] with zero confidence.
+    pub fn classify_fault(
+        &self,
+        group: &PowerGroup,
+        options: &FaultClassificationOptions,
+    ) -> Result<FaultClassification, ComtradeError> {
+        self.check_line_frequency()?;
+        let cycle = 1.0 / self.line_frequency;
+        let trigger = self.trigger_offset();
+
+        let unknown = FaultClassification {
+            fault_type: FaultType::NoFault,
+            confidence: 0.0,
+            inception: None,
+            clearing: None,
+            trip: None,
+            breaker_open: None,
+        };
+        let pre_fault_time = trigger - options.pre_fault_cycles * cycle;
+        let pre_fault = match self.group_phasors_at(group, pre_fault_time, &options.phasor)? {
+            Some(phasors) => phasors,
+            None => return Ok(unknown),
+        };
+        let fault_time = trigger + options.fault_cycles * cycle;
+        let fault = match self.group_phasors_at(group, fault_time, &options.phasor)? {
+            Some(phasors) => phasors,
+            None => return Ok(unknown),
+        };
+
+        let mut current_change = [Phasor::ZERO; 3];
+        let mut voltage_drop = [0.0; 3];
+        for p in 0..3 {
+            current_change[p] = fault.currents[p] - pre_fault.currents[p];
+            voltage_drop[p] =
+                (pre_fault.voltages[p].magnitude() - fault.voltages[p].magnitude()).max(0.0);
+        }
+        let residual_change = current_change[0] + current_change[1] + current_change[2];
+
+        let max_current_change = max_magnitude(current_change.iter().copied());
+        let max_voltage_drop = voltage_drop.iter().copied().fold(0.0, f64::max);
+        let pre_fault_current = max_magnitude(pre_fault.currents.iter().copied());
+        let pre_fault_voltage = max_magnitude(pre_fault.voltages.iter().copied());
+
+        // Is there a fault at all?
+        let current_ratio = if pre_fault_current > 0.0 {
+            max_current_change / pre_fault_current
+        } else if max_current_change > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        };
+        let voltage_ratio = if pre_fault_voltage > 0.0 {
+            max_voltage_drop / pre_fault_voltage
+        } else {
+            0.0
+        };
+        let detection =
+            (current_ratio / options.current_pickup).max(voltage_ratio / options.voltage_pickup);
+        let mut confidence = decision_confidence(detection, 1.0);
+        if detection < 1.0 {
+            return Ok(FaultClassification {
+                confidence,
+                ..unknown
+            });
+        }
+
+        // Which phases are involved? Voltage drops only help pick phases when
+        // there is a significant drop somewhere.
+        let use_voltage = voltage_ratio >= options.voltage_pickup;
+        let mut phases = [false; 3];
+        for p in 0..3 {
+            let mut score = if max_current_change > 0.0 {
+                current_change[p].magnitude() / max_current_change
+            } else {
+                0.0
+            };
+            if use_voltage {
+                score = (score + voltage_drop[p] / max_voltage_drop) / 2.0;
+            }
+            phases[p] = score >= options.phase_selection;
+            confidence = confidence.min(decision_confidence(score, options.phase_selection));
+        }
+
+        let ground_ratio = if max_current_change > 0.0 {
+            residual_change.magnitude() / max_current_change
+        } else {
+            0.0
+        };
+        let ground = ground_ratio >= options.ground_selection;
+        if !ground {
+            if let Some((sequence_phases, sequence_confidence)) =
+                sequence_phase_selection(current_change, options.negative_sequence_selection)
+            {
+                phases = sequence_phases;
+                confidence = confidence.min(sequence_confidence);
+            }
+        }
+        let fault_type = FaultType::from_phases(phases, ground);
+        // Ground doesn't change the classification of single and three phase
+        // faults, so isn't part of the confidence.
+        if phases.iter().filter(|p| **p).count() == 2 {
+            confidence =
+                confidence.min(decision_confidence(ground_ratio, options.ground_selection));
+        }
+
+        let trip = options
+            .roles
+            .as_ref()
+            .and_then(|roles| self.first_assertion(&roles.trip));
+
+        // Inception and clearing from the faulted phase currents.
+        let mut inception: Option<f64> = None;
+        let mut clearing: Option<f64> = None;
+        for p in (0..3).filter(|p| phases[*p]) {
+            let channel = [group.currents.a, group.currents.b, group.currents.c][p];
+            let (first, cleared) =
+                self.current_steps(channel, pre_fault_time, fault_time, trip, options)?;
+            if let Some(first) = first {
+                inception = Some(inception.map_or(first, |i| i.min(first)));
+            }
+            if let Some(cleared) = cleared {
+                clearing = Some(clearing.map_or(cleared, |c| c.max(cleared)));
+            }
+        }
+
+        let breaker_open = options.roles.as_ref().and_then(|roles| {
+            let after = trip.or(inception).unwrap_or(pre_fault_time);
+            self.breaker_open_after(roles, after)
+        });
+
+        Ok(FaultClassification {
+            fault_type,
+            confidence,
+            inception,
+            clearing: clearing.or(breaker_open),
+            trip,
+            breaker_open,
+        })
+    }
+
+    /// Times of the first jump in the cycle-to-cycle difference of a current after
+    /// the pre-fault window (inception), and the next jump at least a cycle after
+    /// that and not before the trip, if known, after which the current stays down
+    /// for a cycle (clearing). Jumps are judged against the change in the
+    /// current's phasor between the pre-fault and fault windows.
+    fn current_steps(
+        &self,
+        channel: usize,
+        pre_fault_time: f64,
+        fault_time: f64,
+        trip: Option<f64>,
+        options: &FaultClassificationOptions,
+    ) -> Result<(Option<f64>, Option<f64>), ComtradeError> {
+        let cycle = 1.0 / self.line_frequency;
+        let data = self.analog_data(channel)?;
+        let unscaled = PhasorOptions {
+            scaling: None,
+            ..options.phasor.clone()
+        };
+        let (change, fault_level) = match (
+            self.phasor_at(channel, pre_fault_time, &unscaled)?,
+            self.phasor_at(channel, fault_time, &unscaled)?,
+        ) {
+            (Some(pre_fault), Some(fault)) => ((fault - pre_fault).magnitude(), fault.magnitude()),
+            _ => return Ok((None, None)),
+        };
+        let threshold = options.waveform_threshold * change * std::f64::consts::SQRT_2;
+
+        let deviation = |n: usize| {
+            let t = self.timestamps[n];
+            (data[n] - interpolate(&self.timestamps, data, t - cycle)).abs()
+        };
+        let squares = SquareIntegral::new(&self.timestamps, data);
+        let stays_down = |n: usize| {
+            let t = self.timestamps[n];
+            squares
+                .rms(t, t + cycle)
+                .is_some_and(|rms| rms < options.clearing_level * fault_level)
+        };
+        let start = self.timestamps.partition_point(|t| *t < pre_fault_time);
+
+        let inception = (start..data.len()).find(|n| deviation(*n) > threshold);
+        let clearing = inception.and_then(|inception| {
+            let from = (self.timestamps[inception] + cycle).max(trip.unwrap_or(f64::MIN));
+            let after = self.timestamps.partition_point(|t| *t < from);
+            (after..data.len()).find(|n| deviation(*n) > threshold && stays_down(*n))
+        });
+
+        Ok((
+            inception.map(|n| self.timestamps[n]),
+            clearing.map(|n| self.timestamps[n]),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fault_type_round_trips_through_phases() {
+        for fault_type in [
+            FaultType::AG,
+            FaultType::BC,
+            FaultType::CAG,
+            FaultType::ABC,
+            FaultType::NoFault,
+        ] {
+            assert_eq!(
+                FaultType::from_phases(fault_type.phases(), fault_type.involves_ground()),
+                fault_type
+            );
+        }
+    }
+
+    #[test]
+    fn decision_confidence_is_clamped() {
+        assert_eq!(decision_confidence(0.5, 0.5), 0.0);
+        assert_eq!(decision_confidence(0.75, 0.5), 0.5);
+        assert_eq!(decision_confidence(10.0, 0.5), 1.0);
+        assert_eq!(decision_confidence(0.0, 0.5), 1.0);
+    }
+}
@@ -0,0 +1,154 @@
+use std::f64::consts::PI;
+
+use crate::analysis::{PhasorOptions, TimeSeries};
+use crate::error::ComtradeError;
+use crate::Comtrade;
+
+/// How to estimate the frequency of a signal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrequencyMethod {
+    /// Time between successive zero crossings in the same direction. Gives an
+    /// estimate every half cycle, timestamped at each crossing.
+    ZeroCrossing,
+
+    /// Rate of change of the angle of the full-cycle phasor, measured over one
+    /// cycle. Gives an estimate at every sample once there are two cycles of data.
+    PhasorAngle,
+}
+
+/// Signal must swing past this fraction of its peak value between zero crossings
+/// for them to count, so noise around zero doesn't give spurious crossings.
+const ZERO_CROSSING_HYSTERESIS: f64 = 0.1;
+
+/// Times at which the signal crosses zero going upwards and downwards, linearly
+/// interpolated between samples.
+fn zero_crossings(timestamps: &[f64], data: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let peak = data.iter().fold(0.0f64, |peak, x| peak.max(x.abs()));
+    let threshold = peak * ZERO_CROSSING_HYSTERESIS;
+
+    let mut rising = Vec::new();
+    let mut falling = Vec::new();
+    let mut armed_rising = false;
+    let mut armed_falling = false;
+
+    for i in 1..data.len() {
+        let (x0, x1) = (data[i - 1], data[i]);
+        if x0 < -threshold {
+            armed_rising = true;
+        }
+        if x0 > threshold {
+            armed_falling = true;
+        }
+
+        let crossing = || {
+            let (t0, t1) = (timestamps[i - 1], timestamps[i]);
+            t0 + (t1 - t0) * x0 / (x0 - x1)
+        };
+        if armed_rising && x0 < 0.0 && x1 >= 0.0 {
+            rising.push(crossing());
+            armed_rising = false;
+        } else if armed_falling && x0 > 0.0 && x1 <= 0.0 {
+            falling.push(crossing());
+            armed_falling = false;
+        }
+    }
+
+    (rising, falling)
+}
+
+impl Comtrade {
+    /// Estimated frequency in Hz of an analog channel over the record. Best used
+    /// on voltage channels, which stay sinusoidal through most disturbances.
+    pub fn frequency(
+        &self,
+        channel: usize,
+        method: FrequencyMethod,
+    ) -> Result<TimeSeries<f64>, ComtradeError> {
+        match method {
+            FrequencyMethod::ZeroCrossing => {
+                let data = self.analog_data(channel)?;
+                let (rising, falling) = zero_crossings(&self.timestamps, data);
+
+                let mut estimates: Vec<(f64, f64)> = rising
+                    .windows(2)
+                    .chain(falling.windows(2))
+                    .map(|pair| (pair[1], 1.0 / (pair[1] - pair[0])))
+                    .collect();
+                estimates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+                let mut series = TimeSeries::new();
+                for (t, f) in estimates {
+                    series.push(t, f);
+                }
+                Ok(series)
+            }
+            FrequencyMethod::PhasorAngle => {
+                let phasors = self.phasors(channel, &PhasorOptions::default())?;
+                let period = 1.0 / self.line_frequency;
+
+                let mut unwrapped = Vec::with_capacity(phasors.len());
+                let mut previous: Option<f64> = None;
+                for phasor in phasors.values.iter() {
+                    let angle = phasor.angle();
+                    let angle = match previous {
+                        Some(previous) => {
+                            previous + (angle - previous + PI).rem_euclid(2.0 * PI) - PI
+                        }
+                        None => angle,
+                    };
+                    unwrapped.push(angle);
+                    previous = Some(angle);
+                }
+
+                let mut series = TimeSeries::new();
+                for (i, t) in phasors.timestamps.iter().enumerate() {
+                    let earlier = phasors
+                        .timestamps
+                        .partition_point(|earlier| *earlier <= t - period + 1e-9);
+                    if earlier == 0 {
+                        continue;
+                    }
+                    let j = earlier - 1;
+                    let dt = t - phasors.timestamps[j];
+                    let rate = (unwrapped[i] - unwrapped[j]) / dt;
+                    series.push(*t, self.line_frequency + rate / (2.0 * PI));
+                }
+                Ok(series)
+            }
+        }
+    }
+
+    /// Tracked frequency at `time`, falling back to the line frequency before
+    /// the first estimate or if the estimate is unusable.
+    pub(crate) fn tracked_frequency_at(&self, frequency: &TimeSeries<f64>, time: f64) -> f64 {
+        match frequency.value_at(time) {
+            Some(f) if f.is_finite() && *f > 0.0 => *f,
+            _ => self.line_frequency,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_crossings_are_interpolated_and_ignore_noise() {
+        let timestamps: Vec<f64> = (0..1000).map(|i| i as f64 / 10000.0).collect();
+        let data: Vec<f64> = timestamps
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let noise = if i % 2 == 0 { 0.01 } else { -0.01 };
+                (2.0 * PI * 52.0 * t).sin() + noise
+            })
+            .collect();
+
+        let (rising, falling) = zero_crossings(&timestamps, &data);
+        assert_eq!(rising.len(), 5);
+        assert_eq!(falling.len(), 5);
+        for pair in rising.windows(2).chain(falling.windows(2)) {
+            assert!((1.0 / (pair[1] - pair[0]) - 52.0).abs() < 0.5);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::f64::consts::{PI, SQRT_2};
+
+use crate::analysis::phasor::integrate;
+use crate::analysis::{Phasor, TimeSeries};
+use crate::error::ComtradeError;
+use crate::Comtrade;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HarmonicOptions {
+    /// Highest harmonic order to calculate. Orders at or above the Nyquist
+    /// frequency of the record are left out.
+    pub max_order: usize,
+
+    /// Length of each analysis window in cycles of the line frequency.
+    pub cycles: usize,
+
+    /// Time between the start of successive windows in cycles of the line
+    /// frequency. Equal to `cycles` for back-to-back windows.
+    pub step_cycles: f64,
+}
+
+impl Default for HarmonicOptions {
+    fn default() -> Self {
+        HarmonicOptions {
+            max_order: 50,
+            cycles: 1,
+            step_cycles: 1.0,
+        }
+    }
+}
+
+/// Harmonic content of a signal over one analysis window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HarmonicSpectrum {
+    /// Phasor of each harmonic, indexed by harmonic order, with RMS magnitudes.
+    /// Index 0 holds the DC component as a real value.
+    pub phasors: Vec<Phasor>,
+}
+
+impl HarmonicSpectrum {
+    /// RMS magnitude of the given harmonic order, or `None` if it wasn't calculated.
+    pub fn magnitude(&self, order: usize) -> Option<f64> {
+        self.phasors.get(order).map(|p| p.magnitude())
+    }
+
+    /// RMS magnitude of the fundamental.
+    pub fn fundamental(&self) -> f64 {
+        self.magnitude(1).unwrap_or(0.0)
+    }
+
+    /// Magnitude of the given harmonic as a fraction of the fundamental, e.g.
+    /// `harmonic_ratio(2)` for the 2nd harmonic restraint used in transformer
+    /// differential protection.
+    pub fn harmonic_ratio(&self, order: usize) -> f64 {
+        self.magnitude(order).unwrap_or(0.0) / self.fundamental()
+    }
+
+    /// RMS of all the harmonics from the 2nd upwards.
+    fn harmonic_rms(&self) -> f64 {
+        self.phasors
+            .iter()
+            .skip(2)
+            .map(|p| p.magnitude().powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Total harmonic distortion as a fraction of the fundamental.
+    pub fn thd(&self) -> f64 {
+        self.harmonic_rms() / self.fundamental()
+    }
+
+    /// Total demand distortion as a fraction of the maximum demand current
+    /// (in the same units as the channel), as defined in IEEE 519.
+    pub fn tdd(&self, max_demand: f64) -> f64 {
+        self.harmonic_rms() / max_demand
+    }
+}
+
+/// Harmonic spectrum of the signal over `start..start + width`, or `None` if the
+/// window isn't within the data.
+fn spectrum(
+    timestamps: &[f64],
+    data: &[f64],
+    start: f64,
+    width: f64,
+    frequency: f64,
+    max_order: usize,
+) -> Option<HarmonicSpectrum> {
+    let first = *timestamps.first()?;
+    let last = *timestamps.last()?;
+    let end = start + width;
+    if start < first - 1e-12 || end > last + 1e-12 {
+        return None;
+    }
+
+    // Harmonics at or above half the sampling rate can't be measured.
+    let lo = timestamps.partition_point(|t| *t < start);
+    let hi = timestamps.partition_point(|t| *t <= end);
+    if hi < lo + 2 {
+        return None;
+    }
+    let rate = (hi - lo - 1) as f64 / (timestamps[hi - 1] - timestamps[lo]);
+    let nyquist_order = ((rate / 2.0) / frequency - 1e-9).floor() as usize;
+    let max_order = max_order.min(nyquist_order);
+
+    let dc = integrate(timestamps, data, start, end, |_| Phasor::new(1.0, 0.0)) / width;
+    let mut phasors = vec![dc];
+    for order in 1..=max_order {
+        let omega = 2.0 * PI * frequency * order as f64;
+        let sum = integrate(timestamps, data, start, end, |t| {
+            Phasor::from_polar(1.0, -omega * t)
+        });
+        phasors.push(sum * (SQRT_2 / width));
+    }
+
+    Some(HarmonicSpectrum { phasors })
+}
+
+impl Comtrade {
+    /// Harmonic spectrum of an analog channel over a window of `options.cycles`
+    /// cycles of the line frequency starting at `start` (seconds relative to the
+    /// start of the record). `None` if the window runs outside the record.
+    pub fn harmonic_spectrum(
+        &self,
+        channel: usize,
+        start: f64,
+        options: &HarmonicOptions,
+    ) -> Result<Option<HarmonicSpectrum>, ComtradeError> {
+        self.check_line_frequency()?;
+        let data = self.analog_data(channel)?;
+        let width = options.cycles as f64 / self.line_frequency;
+        Ok(spectrum(
+            &self.timestamps,
+            data,
+            start,
+            width,
+            self.line_frequency,
+            options.max_order,
+        ))
+    }
+
+    /// Harmonic spectra of an analog channel over the record, from windows of
+    /// `options.cycles` cycles stepped along by `options.step_cycles` cycles.
+    /// Each spectrum is timestamped at the end of its window.
+    pub fn harmonics(
+        &self,
+        channel: usize,
+        options: &HarmonicOptions,
+    ) -> Result<TimeSeries<HarmonicSpectrum>, ComtradeError> {
+        self.check_line_frequency()?;
+        let data = self.analog_data(channel)?;
+        let width = options.cycles as f64 / self.line_frequency;
+        let step = options.step_cycles / self.line_frequency;
+
+        let mut series = TimeSeries::new();
+        let first = match self.timestamps.first() {
+            Some(first) => *first,
+            None => return Ok(series),
+        };
+        if !step.is_finite() || step <= 0.0 {
+            return Ok(series);
+        }
+
+        let mut n = 0;
+        while let Some(spectrum) = spectrum(
+            &self.timestamps,
+            data,
+            first + n as f64 * step,
+            width,
+            self.line_frequency,
+            options.max_order,
+        ) {
+            series.push(first + n as f64 * step + width, spectrum);
+            n += 1;
+        }
+        Ok(series)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spectrum_of_distorted_wave() {
+        let rate = 3840.0;
+        let timestamps: Vec<f64> = (0..640).map(|i| i as f64 / rate).collect();
+        let data: Vec<f64> = timestamps
+            .iter()
+            .map(|t| {
+                let w = 2.0 * PI * 60.0 * t;
+                2.0 + 100.0 * SQRT_2 * w.cos()
+                    + 15.0 * SQRT_2 * (2.0 * w).sin()
+                    + 5.0 * SQRT_2 * (5.0 * w + 1.0).cos()
+            })
+            .collect();
+
+        let spectrum = spectrum(&timestamps, &data, 0.0125, 2.0 / 60.0, 60.0, 100).unwrap();
+
+        // 64 samples per cycle, so up to the 31st harmonic.
+        assert_eq!(spectrum.phasors.len(), 32);
+        assert!((spectrum.phasors[0].re - 2.0).abs() < 1e-6);
+        assert!((spectrum.fundamental() - 100.0).abs() < 1e-6);
+        assert!((spectrum.harmonic_ratio(2) - 0.15).abs() < 1e-6);
+        assert!((spectrum.magnitude(5).unwrap() - 5.0).abs() < 1e-6);
+        assert!(spectrum.magnitude(3).unwrap() < 1e-6);
+
+        let thd = (15.0f64.powi(2) + 5.0f64.powi(2)).sqrt() / 100.0;
+        assert!((spectrum.thd() - thd).abs() < 1e-6);
+        assert!((spectrum.tdd(200.0) - thd / 2.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,262 @@
+use crate::analysis::{Phasor, PhasorOptions, PowerGroup, TimeSeries};
+use crate::error::ComtradeError;
+use crate::Comtrade;
+
+/// Measuring loop of a distance element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultLoop {
+    AG,
+    BG,
+    CG,
+    AB,
+    BC,
+    CA,
+}
+
+impl FaultLoop {
+    pub const ALL: [FaultLoop; 6] = [
+        FaultLoop::AG,
+        FaultLoop::BG,
+        FaultLoop::CG,
+        FaultLoop::AB,
+        FaultLoop::BC,
+        FaultLoop::CA,
+    ];
+
+    /// Whether this is a phase-to-ground loop.
+    pub fn is_ground(&self) -> bool {
+        matches!(self, FaultLoop::AG | FaultLoop::BG | FaultLoop::CG)
+    }
+
+    /// Voltage and residual compensated current measured by the loop, from the
+    /// phase A, B and C voltages and currents.
+    pub(crate) fn quantities(
+        &self,
+        v: [Phasor; 3],
+        i: [Phasor; 3],
+        k0: Phasor,
+    ) -> (Phasor, Phasor) {
+        let residual = i[0] + i[1] + i[2];
+        match self {
+            FaultLoop::AG => (v[0], i[0] + k0 * residual),
+            FaultLoop::BG => (v[1], i[1] + k0 * residual),
+            FaultLoop::CG => (v[2], i[2] + k0 * residual),
+            FaultLoop::AB => (v[0] - v[1], i[0] - i[1]),
+            FaultLoop::BC => (v[1] - v[2], i[1] - i[2]),
+            FaultLoop::CA => (v[2] - v[0], i[2] - i[0]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImpedanceOptions {
+    pub phasor: PhasorOptions,
+
+    /// Residual compensation factor `k0 = (Z0 - Z1) / 3 Z1` for the ground loops.
+    pub k0: Phasor,
+}
+
+impl Default for ImpedanceOptions {
+    fn default() -> Self {
+        ImpedanceOptions {
+            phasor: PhasorOptions::default(),
+            k0: Phasor::ZERO,
+        }
+    }
+}
+
+/// Operating characteristic of a distance zone on the R-X plane, with reaches in
+/// ohms and angles in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZoneCharacteristic {
+    /// Circle through the origin (or through the reverse reach behind it) with its
+    /// diameter along the characteristic angle.
+    Mho {
+        reach: f64,
+        angle: f64,
+        reverse_reach: f64,
+    },
+
+    /// Polygon bounded by the reactance reach above, the reverse reactance reach
+    /// below and resistive blinders either side, parallel to the line angle. The
+    /// line angle must be between 0° and 180°, exclusive; the blinders of a zone at
+    /// 0° would lie along the resistance axis, so it contains nothing off it.
+    Quadrilateral {
+        reactance_reach: f64,
+        resistive_reach: f64,
+        line_angle: f64,
+        reverse_reach: f64,
+    },
+}
+
+impl ZoneCharacteristic {
+    /// Whether the impedance lies inside (or on the boundary of) the zone.
+    pub fn contains(&self, impedance: Phasor) -> bool {
+        match *self {
+            ZoneCharacteristic::Mho {
+                reach,
+                angle,
+                reverse_reach,
+            } => {
+                let angle = angle.to_radians();
+                let centre = Phasor::from_polar((reach - reverse_reach) / 2.0, angle);
+                let radius = (reach + reverse_reach) / 2.0;
+                (impedance - centre).magnitude() <= radius
+            }
+            ZoneCharacteristic::Quadrilateral {
+                reactance_reach,
+                resistive_reach,
+                line_angle,
+                reverse_reach,
+            } => {
+                // Resistance measured horizontally from the line impedance is the
+                // perpendicular distance from the line over sin(line angle).
+                // Comparing the distances multiplied through by sin avoids
+                // dividing by tan, which blows up at 0° and loses precision
+                // towards 90°.
+                let (sin, cos) = line_angle.to_radians().sin_cos();
+                let distance = impedance.re * sin - impedance.im * cos;
+                impedance.im <= reactance_reach
+                    && impedance.im >= -reverse_reach
+                    && distance.abs() <= resistive_reach * sin
+            }
+        }
+    }
+}
+
+/// Apparent impedance seen by one measuring loop over time, in ohms as a complex
+/// number (`re` is resistance, `im` is reactance).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImpedanceTrajectory {
+    pub fault_loop: FaultLoop,
+    pub impedance: TimeSeries<Phasor>,
+}
+
+impl ImpedanceTrajectory {
+    pub fn resistance(&self) -> TimeSeries<f64> {
+        self.impedance.map(|z| z.re)
+    }
+
+    pub fn reactance(&self) -> TimeSeries<f64> {
+        self.impedance.map(|z| z.im)
+    }
+
+    /// Whether the impedance is inside the zone at each time.
+    pub fn in_zone(&self, zone: &ZoneCharacteristic) -> TimeSeries<bool> {
+        self.impedance.map(|z| zone.contains(*z))
+    }
+
+    /// First time the impedance enters the zone.
+    pub fn zone_entry(&self, zone: &ZoneCharacteristic) -> Option<f64> {
+        self.impedance
+            .iter()
+            .find(|(_, z)| zone.contains(**z))
+            .map(|(t, _)| t)
+    }
+}
+
+impl Comtrade {
+    /// Apparent impedance seen by a measuring loop, from the phase-to-neutral
+    /// voltages and currents of a circuit. Ground loops use `V / (I + k0 * 3I0)`
+    /// with the residual current summed from the phases, and phase loops use the
+    /// phase-to-phase voltage over the difference in currents. Times where the loop
+    /// current is zero are left out.
+    pub fn apparent_impedance(
+        &self,
+        group: &PowerGroup,
+        fault_loop: FaultLoop,
+        options: &ImpedanceOptions,
+    ) -> Result<ImpedanceTrajectory, ComtradeError> {
+        let v = [
+            self.si_phasors(group.voltages.a, "V", &options.phasor)?,
+            self.si_phasors(group.voltages.b, "V", &options.phasor)?,
+            self.si_phasors(group.voltages.c, "V", &options.phasor)?,
+        ];
+        let i = [
+            self.si_phasors(group.currents.a, "A", &options.phasor)?,
+            self.si_phasors(group.currents.b, "A", &options.phasor)?,
+            self.si_phasors(group.currents.c, "A", &options.phasor)?,
+        ];
+
+        let mut impedance = TimeSeries::new();
+        for (n, timestamp) in v[0].timestamps.iter().enumerate() {
+            let v = [v[0].values[n], v[1].values[n], v[2].values[n]];
+            let i = [i[0].values[n], i[1].values[n], i[2].values[n]];
+            let (voltage, current) = fault_loop.quantities(v, i, options.k0);
+            if current.magnitude() > 0.0 {
+                impedance.push(*timestamp, voltage / current);
+            }
+        }
+
+        Ok(ImpedanceTrajectory {
+            fault_loop,
+            impedance,
+        })
+    }
+
+    /// Apparent impedance seen by all six measuring loops.
+    pub fn impedance_loops(
+        &self,
+        group: &PowerGroup,
+        options: &ImpedanceOptions,
+    ) -> Result<Vec<ImpedanceTrajectory>, ComtradeError> {
+        FaultLoop::ALL
+            .iter()
+            .map(|fault_loop| self.apparent_impedance(group, *fault_loop, options))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mho_contains_points_within_circle() {
+        let zone = ZoneCharacteristic::Mho {
+            reach: 10.0,
+            angle: 90.0,
+            reverse_reach: 0.0,
+        };
+        assert!(zone.contains(Phasor::new(0.0, 5.0)));
+        assert!(zone.contains(Phasor::new(4.0, 5.0)));
+        assert!(!zone.contains(Phasor::new(0.0, 11.0)));
+        assert!(!zone.contains(Phasor::new(0.0, -1.0)));
+        assert!(!zone.contains(Phasor::new(5.0, 1.0)));
+    }
+
+    #[test]
+    fn quadrilateral_contains_points_within_blinders() {
+        let zone = ZoneCharacteristic::Quadrilateral {
+            reactance_reach: 10.0,
+            resistive_reach: 5.0,
+            line_angle: 45.0,
+            reverse_reach: 1.0,
+        };
+        assert!(zone.contains(Phasor::new(8.0, 5.0)));
+        assert!(zone.contains(Phasor::new(0.0, -0.5)));
+        assert!(!zone.contains(Phasor::new(11.0, 5.0)));
+        assert!(!zone.contains(Phasor::new(0.0, 11.0)));
+        assert!(!zone.contains(Phasor::new(0.0, -2.0)));
+
+        // Blinders straight up at 90°, where tan is infinite.
+        let zone = ZoneCharacteristic::Quadrilateral {
+            reactance_reach: 10.0,
+            resistive_reach: 5.0,
+            line_angle: 90.0,
+            reverse_reach: 1.0,
+        };
+        assert!(zone.contains(Phasor::new(-4.99, 9.0)));
+        assert!(!zone.contains(Phasor::new(5.01, 1.0)));
+
+        // Degenerate at 0°, but no NaNs.
+        let zone = ZoneCharacteristic::Quadrilateral {
+            reactance_reach: 10.0,
+            resistive_reach: 5.0,
+            line_angle: 0.0,
+            reverse_reach: 1.0,
+        };
+        assert!(zone.contains(Phasor::new(3.0, 0.0)));
+        assert!(!zone.contains(Phasor::new(3.0, 0.5)));
+    }
+}
@@ -0,0 +1,183 @@
+use crate::analysis::HarmonicOptions;
+use crate::error::ComtradeError;
+use crate::Comtrade;
+
+/// Period of a channel where a condition was detected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectionInterval {
+    /// Times in seconds relative to the start of the record, from the start of
+    /// the first analysis window to the end of the last.
+    pub start: f64,
+    pub end: f64,
+
+    /// From 0 to 1, the average over the analysis windows of the fraction of the
+    /// detection criteria which were met.
+    pub confidence: f64,
+}
+
+/// Join overlapping windows `(start, end, score)` with a score above zero into
+/// intervals.
+pub(crate) fn detection_intervals(windows: &[(f64, f64, f64)]) -> Vec<DetectionInterval> {
+    let mut intervals: Vec<DetectionInterval> = Vec::new();
+    let mut count = 0;
+    for (start, end, score) in windows.iter().filter(|w| w.2 > 0.0) {
+        match intervals.last_mut() {
+            Some(last) if *start <= last.end => {
+                last.end = *end;
+                last.confidence += score;
+                count += 1;
+            }
+            _ => {
+                if let Some(last) = intervals.last_mut() {
+                    last.confidence /= count as f64;
+                }
+                intervals.push(DetectionInterval {
+                    start: *start,
+                    end: *end,
+                    confidence: *score,
+                });
+                count = 1;
+            }
+        }
+    }
+    if let Some(last) = intervals.last_mut() {
+        last.confidence /= count as f64;
+    }
+    intervals
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InrushOptions {
+    /// Windows whose peak absolute value is below this, in the units the channel
+    /// was recorded in, are ignored. Set it above the noise level.
+    pub pickup: f64,
+
+    /// 2nd harmonic as a fraction of the fundamental at or above which a window
+    /// looks like inrush.
+    pub second_harmonic_ratio: f64,
+
+    /// Values within this fraction of the window's peak of zero count as a gap
+    /// in the waveform.
+    pub gap_threshold: f64,
+
+    /// Length of gap, in degrees of the line frequency, at or above which a
+    /// window looks like inrush.
+    pub min_gap_degrees: f64,
+}
+
+impl Default for InrushOptions {
+    fn default() -> Self {
+        InrushOptions {
+            pickup: 0.0,
+            second_harmonic_ratio: 0.15,
+            gap_threshold: 0.05,
+            min_gap_degrees: 60.0,
+        }
+    }
+}
+
+impl Comtrade {
+    /// Start and end times of one cycle windows stepped along the record by half a
+    /// cycle.
+    pub(crate) fn half_cycle_steps(&self) -> Vec<(f64, f64)> {
+        let (first, last) = match (self.timestamps.first(), self.timestamps.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return Vec::new(),
+        };
+        let cycle = 1.0 / self.line_frequency;
+
+        let mut windows = Vec::new();
+        let mut n = 0;
+        while first + (n as f64 * 0.5 + 1.0) * cycle <= last + 1e-12 {
+            let start = first + n as f64 * 0.5 * cycle;
+            windows.push((start, start + cycle));
+            n += 1;
+        }
+        windows
+    }
+
+    /// Find transformer magnetising inrush in a current channel, from one cycle
+    /// windows stepped along by half a cycle. A window looks like inrush if it has
+    /// a large 2nd harmonic or gaps where the current stays near zero, and each
+    /// of these counts for half of the confidence. CT saturation can also leave
+    /// gaps, see [`Comtrade::detect_ct_saturation`].
+    pub fn detect_inrush(
+        &self,
+        channel: usize,
+        options: &InrushOptions,
+    ) -> Result<Vec<DetectionInterval>, ComtradeError> {
+        self.check_line_frequency()?;
+        let data = self.analog_data(channel)?;
+        let harmonic_options = HarmonicOptions {
+            max_order: 2,
+            cycles: 1,
+            step_cycles: 1.0,
+        };
+
+        let mut windows = Vec::new();
+        for (start, end) in self.half_cycle_steps() {
+            let lo = self.timestamps.partition_point(|t| *t < start);
+            let hi = self.timestamps.partition_point(|t| *t <= end);
+            let peak = data[lo..hi].iter().fold(0.0, |peak, x| x.abs().max(peak));
+            if peak <= options.pickup || peak == 0.0 {
+                windows.push((start, end, 0.0));
+                continue;
+            }
+
+            let harmonic = self
+                .harmonic_spectrum(channel, start, &harmonic_options)?
+                .is_some_and(|s| s.harmonic_ratio(2) >= options.second_harmonic_ratio);
+
+            // Longest run of samples near zero.
+            let mut gap = 0.0;
+            let mut longest: f64 = 0.0;
+            let level = options.gap_threshold * peak;
+            for n in lo + 1..hi {
+                if data[n].abs() <= level && data[n - 1].abs() <= level {
+                    gap += self.timestamps[n] - self.timestamps[n - 1];
+                    longest = longest.max(gap);
+                } else {
+                    gap = 0.0;
+                }
+            }
+            let gaps = longest * self.line_frequency * 360.0 >= options.min_gap_degrees;
+
+            let score = [harmonic, gaps].iter().filter(|c| **c).count() as f64 / 2.0;
+            windows.push((start, end, score));
+        }
+
+        Ok(detection_intervals(&windows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_windows_are_joined_and_confidence_averaged() {
+        let windows = [
+            (0.0, 1.0, 0.0),
+            (0.5, 1.5, 1.0),
+            (1.0, 2.0, 0.5),
+            (1.5, 2.5, 0.0),
+            (2.0, 3.0, 0.0),
+            (2.5, 3.5, 0.5),
+        ];
+        assert_eq!(
+            detection_intervals(&windows),
+            vec![
+                DetectionInterval {
+                    start: 0.5,
+                    end: 2.0,
+                    confidence: 0.75
+                },
+                DetectionInterval {
+                    start: 2.5,
+                    end: 3.5,
+                    confidence: 0.5
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! Signal processing and power system analysis built on top of parsed records.
+
+mod disturbance;
+mod fault_location;
+mod fault_type;
+mod frequency;
+mod harmonics;
+mod impedance;
+mod inrush;
+mod phasor;
+mod power;
+mod rms;
+mod sag;
+mod saturation;
+mod sequence;
+
+pub use disturbance::{Disturbance, DisturbanceOptions, DisturbanceTrigger, Threshold};
+pub use fault_location::{
+    FaultLocation, FaultLocationMethod, FaultLocationOptions, LineParameters,
+};
+pub use fault_type::{FaultClassification, FaultClassificationOptions, FaultType};
+pub use frequency::FrequencyMethod;
+pub use harmonics::{HarmonicOptions, HarmonicSpectrum};
+pub use impedance::{FaultLoop, ImpedanceOptions, ImpedanceTrajectory, ZoneCharacteristic};
+pub use inrush::{DetectionInterval, InrushOptions};
+pub use phasor::{Phasor, PhasorFilter, PhasorOptions, PhasorWindow};
+pub use power::{Energy, PowerGroup, PowerQuantities};
+pub use rms::RmsWindow;
+pub use sag::{SagSwellOptions, SagType, VoltageEvent, VoltageEventKind};
+pub use saturation::SaturationOptions;
+pub use sequence::{PhaseGroup, Sequence, SequenceComponents};
+
+/// Values calculated at a series of times, in seconds relative to the start of
+/// the record they were calculated from.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TimeSeries<T> {
+    pub timestamps: Vec<f64>,
+    pub values: Vec<T>,
+}
+
+impl<T> TimeSeries<T> {
+    pub fn new() -> Self {
+        TimeSeries {
+            timestamps: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn push(&mut self, timestamp: f64, value: T) {
+        self.timestamps.push(timestamp);
+        self.values.push(value);
+    }
+
+    /// Iterate over `(timestamp, value)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (f64, &T)> {
+        self.timestamps.iter().copied().zip(self.values.iter())
+    }
+
+    /// Value at the last time at or before `time`.
+    pub fn value_at(&self, time: f64) -> Option<&T> {
+        let after = self.timestamps.partition_point(|t| *t <= time);
+        if after == 0 {
+            return None;
+        }
+        self.values.get(after - 1)
+    }
+
+    /// Apply a function to every value, keeping the timestamps.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> TimeSeries<U> {
+        TimeSeries {
+            timestamps: self.timestamps.clone(),
+            values: self.values.iter().map(f).collect(),
+        }
+    }
+}
@@ -0,0 +1,475 @@
+use std::f64::consts::{PI, SQRT_2};
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub};
+
+use crate::analysis::TimeSeries;
+use crate::error::ComtradeError;
+use crate::{AnalogScalingMode, Comtrade};
+
+/// A complex number representing the magnitude and angle of a sinusoid.
+///
+/// Phasors calculated from records use RMS magnitudes and angles relative to a
+/// cosine at the analysis frequency starting at the first sample of the record.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Phasor {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Phasor {
+    pub const ZERO: Phasor = Phasor { re: 0.0, im: 0.0 };
+
+    pub fn new(re: f64, im: f64) -> Self {
+        Phasor { re, im }
+    }
+
+    /// Phasor from magnitude and angle in radians.
+    pub fn from_polar(magnitude: f64, angle: f64) -> Self {
+        Phasor {
+            re: magnitude * angle.cos(),
+            im: magnitude * angle.sin(),
+        }
+    }
+
+    pub fn magnitude(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// Angle in radians, between -π and π.
+    pub fn angle(&self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    /// Angle in degrees, between -180 and 180.
+    pub fn angle_degrees(&self) -> f64 {
+        self.angle().to_degrees()
+    }
+
+    pub fn conj(&self) -> Self {
+        Phasor {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+
+    /// Rotate by the given angle in radians.
+    pub fn rotate(&self, angle: f64) -> Self {
+        *self * Phasor::from_polar(1.0, angle)
+    }
+}
+
+impl Add for Phasor {
+    type Output = Phasor;
+
+    fn add(self, other: Phasor) -> Phasor {
+        Phasor::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl AddAssign for Phasor {
+    fn add_assign(&mut self, other: Phasor) {
+        self.re += other.re;
+        self.im += other.im;
+    }
+}
+
+impl Sub for Phasor {
+    type Output = Phasor;
+
+    fn sub(self, other: Phasor) -> Phasor {
+        Phasor::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Phasor {
+    type Output = Phasor;
+
+    fn mul(self, other: Phasor) -> Phasor {
+        Phasor::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Mul<f64> for Phasor {
+    type Output = Phasor;
+
+    fn mul(self, factor: f64) -> Phasor {
+        Phasor::new(self.re * factor, self.im * factor)
+    }
+}
+
+impl Div for Phasor {
+    type Output = Phasor;
+
+    fn div(self, other: Phasor) -> Phasor {
+        let denominator = other.re * other.re + other.im * other.im;
+        Phasor::new(
+            (self.re * other.re + self.im * other.im) / denominator,
+            (self.im * other.re - self.re * other.im) / denominator,
+        )
+    }
+}
+
+impl Div<f64> for Phasor {
+    type Output = Phasor;
+
+    fn div(self, divisor: f64) -> Phasor {
+        Phasor::new(self.re / divisor, self.im / divisor)
+    }
+}
+
+impl Neg for Phasor {
+    type Output = Phasor;
+
+    fn neg(self) -> Phasor {
+        Phasor::new(-self.re, -self.im)
+    }
+}
+
+/// Length of the data window used to estimate each phasor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PhasorWindow {
+    /// One cycle of the analysis frequency. Rejects DC offset and all harmonics.
+    FullCycle,
+
+    /// Half a cycle of the analysis frequency. Responds twice as quickly but does
+    /// not reject DC offset or even harmonics.
+    HalfCycle,
+}
+
+impl PhasorWindow {
+    /// Length of the window in cycles.
+    pub fn cycles(&self) -> f64 {
+        match self {
+            PhasorWindow::FullCycle => 1.0,
+            PhasorWindow::HalfCycle => 0.5,
+        }
+    }
+}
+
+/// Filter used to extract the fundamental from the data window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PhasorFilter {
+    /// Correlate with a sine and cosine over the window (i.e. a DFT).
+    Fourier,
+
+    /// Correlate with a cosine only, taking the imaginary part from the output a
+    /// quarter of a cycle earlier. This gives better rejection of decaying DC
+    /// offset at the expense of an extra quarter cycle of delay.
+    Cosine,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhasorOptions {
+    pub window: PhasorWindow,
+    pub filter: PhasorFilter,
+
+    /// Convert values to primary or secondary units. `None` keeps values in the
+    /// units they were recorded in.
+    pub scaling: Option<AnalogScalingMode>,
+}
+
+impl Default for PhasorOptions {
+    fn default() -> Self {
+        PhasorOptions {
+            window: PhasorWindow::FullCycle,
+            filter: PhasorFilter::Fourier,
+            scaling: None,
+        }
+    }
+}
+
+/// Value of the sampled signal at `time` by linear interpolation. `time` must be
+/// within the sampled range.
+pub(crate) fn interpolate(timestamps: &[f64], data: &[f64], time: f64) -> f64 {
+    let after = timestamps.partition_point(|t| *t < time);
+    if after == 0 {
+        return data[0];
+    }
+    if after == timestamps.len() {
+        return data[after - 1];
+    }
+
+    let before = after - 1;
+    let (t0, t1) = (timestamps[before], timestamps[after]);
+    if t1 == t0 {
+        return data[after];
+    }
+    data[before] + (data[after] - data[before]) * (time - t0) / (t1 - t0)
+}
+
+/// Integrate `x(t) * kernel(t)` over `start..end` using the trapezoidal rule,
+/// interpolating the signal at the ends of the interval. Working in time rather
+/// than samples means windows don't need to be a whole number of samples, and
+/// copes with sampling rate changes and critical timestamps.
+pub(crate) fn integrate(
+    timestamps: &[f64],
+    data: &[f64],
+    start: f64,
+    end: f64,
+    kernel: impl Fn(f64) -> Phasor,
+) -> Phasor {
+    let first = timestamps.partition_point(|t| *t <= start);
+    let last = timestamps.partition_point(|t| *t < end);
+
+    let mut total = Phasor::ZERO;
+    let mut previous = (start, kernel(start) * interpolate(timestamps, data, start));
+    let inner = timestamps[first..last]
+        .iter()
+        .zip(data[first..last].iter())
+        .map(|(t, x)| (*t, kernel(*t) * *x));
+    let end_point = (end, kernel(end) * interpolate(timestamps, data, end));
+
+    for (t, value) in inner.chain(std::iter::once(end_point)) {
+        total += (previous.1 + value) * (0.5 * (t - previous.0));
+        previous = (t, value);
+    }
+
+    total
+}
+
+/// Estimate the phasor of the signal at `frequency` Hz from the window ending at
+/// time `end`. Returns `None` if there isn't enough data before `end`.
+pub(crate) fn estimate_phasor(
+    timestamps: &[f64],
+    data: &[f64],
+    end: f64,
+    frequency: f64,
+    window: PhasorWindow,
+    filter: PhasorFilter,
+) -> Option<Phasor> {
+    let first = *timestamps.first()?;
+    let last = *timestamps.last()?;
+    let omega = 2.0 * PI * frequency;
+    let width = window.cycles() / frequency;
+
+    match filter {
+        PhasorFilter::Fourier => {
+            if end - width < first - 1e-12 || end > last + 1e-12 {
+                return None;
+            }
+            let sum = integrate(timestamps, data, end - width, end, |t| {
+                Phasor::from_polar(1.0, -omega * t)
+            });
+            Some(sum * (SQRT_2 / width))
+        }
+        PhasorFilter::Cosine => {
+            let quarter_cycle = 0.25 / frequency;
+            if end - width - quarter_cycle < first - 1e-12 || end > last + 1e-12 {
+                return None;
+            }
+
+            // Output of the cosine filter is the instantaneous value of the
+            // fundamental at the end of the window.
+            let cosine_filter = |window_end: f64| {
+                integrate(timestamps, data, window_end - width, window_end, |t| {
+                    Phasor::new((omega * (t - window_end)).cos(), 0.0)
+                })
+                .re * (2.0 / width)
+            };
+
+            let rotating = Phasor::new(cosine_filter(end), cosine_filter(end - quarter_cycle));
+            Some(rotating.rotate(-omega * end) / SQRT_2)
+        }
+    }
+}
+
+impl Comtrade {
+    pub(crate) fn check_line_frequency(&self) -> Result<(), ComtradeError> {
+        if !self.line_frequency.is_finite() || self.line_frequency <= 0.0 {
+            return Err(ComtradeError::InvalidLineFrequency(self.line_frequency));
+        }
+        Ok(())
+    }
+
+    /// Phasor of an analog channel at the line frequency, estimated from the
+    /// window ending at `time` (seconds relative to the start of the record).
+    /// `None` if there isn't a full window of data before `time`.
+    pub fn phasor_at(
+        &self,
+        channel: usize,
+        time: f64,
+        options: &PhasorOptions,
+    ) -> Result<Option<Phasor>, ComtradeError> {
+        self.check_line_frequency()?;
+        let analog = self
+            .analog_channels
+            .get(channel)
+            .ok_or(ComtradeError::AnalogChannelNotFound(channel))?;
+        let factor = options
+            .scaling
+            .map_or(1.0, |mode| analog.config.scaling_factor(&mode));
+
+        Ok(estimate_phasor(
+            &self.timestamps,
+            &analog.data,
+            time,
+            self.line_frequency,
+            options.window,
+            options.filter,
+        )
+        .map(|p| p * factor))
+    }
+
+    /// Phasors of an analog channel at the line frequency, using a window sliding
+    /// along the record a sample at a time. There is a phasor for every sample
+    /// from the first one with a full window of data before it.
+    pub fn phasors(
+        &self,
+        channel: usize,
+        options: &PhasorOptions,
+    ) -> Result<TimeSeries<Phasor>, ComtradeError> {
+        self.check_line_frequency()?;
+        let line_frequency = self.line_frequency;
+        self.sliding_phasors(channel, options, |_| line_frequency)
+    }
+
+    /// Phasors of an analog channel like [`Comtrade::phasors`], but with the
+    /// window length and analysis frequency following the tracked `frequency`
+    /// (e.g. from [`Comtrade::frequency`]) rather than the nominal line frequency.
+    /// The line frequency is used until the first frequency estimate.
+    pub fn phasors_tracking(
+        &self,
+        channel: usize,
+        options: &PhasorOptions,
+        frequency: &TimeSeries<f64>,
+    ) -> Result<TimeSeries<Phasor>, ComtradeError> {
+        self.check_line_frequency()?;
+        self.sliding_phasors(channel, options, |t| {
+            self.tracked_frequency_at(frequency, t)
+        })
+    }
+
+    fn sliding_phasors(
+        &self,
+        channel: usize,
+        options: &PhasorOptions,
+        frequency_at: impl Fn(f64) -> f64,
+    ) -> Result<TimeSeries<Phasor>, ComtradeError> {
+        let analog = self
+            .analog_channels
+            .get(channel)
+            .ok_or(ComtradeError::AnalogChannelNotFound(channel))?;
+        let factor = options
+            .scaling
+            .map_or(1.0, |mode| analog.config.scaling_factor(&mode));
+
+        let mut series = TimeSeries::new();
+        for t in self.timestamps.iter() {
+            if let Some(phasor) = estimate_phasor(
+                &self.timestamps,
+                &analog.data,
+                *t,
+                frequency_at(*t),
+                options.window,
+                options.filter,
+            ) {
+                series.push(*t, phasor * factor);
+            }
+        }
+
+        Ok(series)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(
+        rate: f64,
+        frequency: f64,
+        magnitude: f64,
+        angle: f64,
+        n: usize,
+    ) -> (Vec<f64>, Vec<f64>) {
+        let timestamps: Vec<f64> = (0..n).map(|i| i as f64 / rate).collect();
+        let data = timestamps
+            .iter()
+            .map(|t| magnitude * SQRT_2 * (2.0 * PI * frequency * t + angle).cos())
+            .collect();
+        (timestamps, data)
+    }
+
+    fn assert_phasor_close(actual: Phasor, magnitude: f64, angle: f64) {
+        let expected = Phasor::from_polar(magnitude, angle);
+        assert!(
+            (actual - expected).magnitude() < magnitude * 1e-3,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn phasor_arithmetic() {
+        let a = Phasor::new(3.0, 4.0);
+        assert_eq!(a.magnitude(), 5.0);
+        assert_eq!(a * Phasor::new(0.0, 1.0), Phasor::new(-4.0, 3.0));
+        let quotient = (a / a) - Phasor::new(1.0, 0.0);
+        assert!(quotient.magnitude() < 1e-12);
+        assert_eq!(a.conj(), Phasor::new(3.0, -4.0));
+    }
+
+    #[test]
+    fn full_cycle_fourier_with_integer_samples_per_cycle() {
+        let (t, x) = sine(960.0, 60.0, 100.0, 0.5, 64);
+        for end in t[16..].iter() {
+            let p = estimate_phasor(
+                &t,
+                &x,
+                *end,
+                60.0,
+                PhasorWindow::FullCycle,
+                PhasorFilter::Fourier,
+            );
+            assert_phasor_close(p.unwrap(), 100.0, 0.5);
+        }
+        assert!(estimate_phasor(
+            &t,
+            &x,
+            t[15],
+            60.0,
+            PhasorWindow::FullCycle,
+            PhasorFilter::Fourier
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn fourier_and_cosine_with_non_integer_samples_per_cycle() {
+        // 4000 / 60 = 66.67 samples per cycle.
+        let (t, x) = sine(4000.0, 60.0, 10.0, -2.0, 400);
+        for window in [PhasorWindow::FullCycle, PhasorWindow::HalfCycle] {
+            for filter in [PhasorFilter::Fourier, PhasorFilter::Cosine] {
+                let p = estimate_phasor(&t, &x, t[300], 60.0, window, filter).unwrap();
+                assert_phasor_close(p, 10.0, -2.0);
+            }
+        }
+    }
+
+    #[test]
+    fn full_cycle_rejects_dc_offset() {
+        let (t, x) = sine(4800.0, 50.0, 1.0, 1.0, 400);
+        let x: Vec<f64> = x.iter().map(|v| v + 5.0).collect();
+        let p = estimate_phasor(
+            &t,
+            &x,
+            t[200],
+            50.0,
+            PhasorWindow::FullCycle,
+            PhasorFilter::Fourier,
+        );
+        assert_phasor_close(p.unwrap(), 1.0, 1.0);
+        let p = estimate_phasor(
+            &t,
+            &x,
+            t[200],
+            50.0,
+            PhasorWindow::FullCycle,
+            PhasorFilter::Cosine,
+        );
+        assert_phasor_close(p.unwrap(), 1.0, 1.0);
+    }
+}
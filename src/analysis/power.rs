@@ -0,0 +1,331 @@
+use crate::analysis::{PhaseGroup, Phasor, PhasorOptions, TimeSeries};
+use crate::error::ComtradeError;
+use crate::query::normalise_field;
+use crate::{AnalogConfig, Comtrade};
+
+/// Factor to convert values in `units` into the SI base unit `base`, e.g. 1000
+/// for kV when `base` is V. `None` if the units aren't a recognised prefix of
+/// `base`. The base unit is matched ignoring case and "K" is accepted for kilo,
+/// as many recorders write units like "KV" or "kv".
+pub(crate) fn si_factor(units: &str, base: &str) -> Option<f64> {
+    let units = units.trim();
+    let split = units.len().checked_sub(base.len())?;
+    let (prefix, unit) = (units.get(..split)?, units.get(split..)?);
+    if !unit.eq_ignore_ascii_case(base) {
+        return None;
+    }
+    match prefix {
+        "" => Some(1.0),
+        "m" => Some(1e-3),
+        "k" | "K" => Some(1e3),
+        "M" => Some(1e6),
+        _ => None,
+    }
+}
+
+fn si_scaling(
+    config: &AnalogConfig,
+    base: &str,
+    options: &PhasorOptions,
+) -> Result<f64, ComtradeError> {
+    let unit_factor = si_factor(&config.units, base)
+        .ok_or_else(|| ComtradeError::UnrecognisedUnits(config.units.trim().to_string()))?;
+    let scaling_factor = options
+        .scaling
+        .map_or(1.0, |mode| config.scaling_factor(&mode));
+    Ok(unit_factor * scaling_factor)
+}
+
+/// Power flow calculated from voltage and current phasors.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PowerQuantities {
+    /// Active power in W.
+    pub active: f64,
+    /// Reactive power in var, positive for lagging (inductive) current.
+    pub reactive: f64,
+    /// Apparent power in VA.
+    pub apparent: f64,
+    /// Ratio of active to apparent power, signed by the direction of active
+    /// power flow. Zero when there is no apparent power.
+    pub power_factor: f64,
+}
+
+impl PowerQuantities {
+    /// Power quantities from complex power `S = V I*`, in VA.
+    pub fn from_complex_power(power: Phasor) -> Self {
+        let apparent = power.magnitude();
+        PowerQuantities {
+            active: power.re,
+            reactive: power.im,
+            apparent,
+            power_factor: if apparent > 0.0 {
+                power.re / apparent
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// Energy transferred over a period.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Energy {
+    /// Active energy in Wh.
+    pub active: f64,
+    /// Reactive energy in varh.
+    pub reactive: f64,
+}
+
+impl TimeSeries<PowerQuantities> {
+    /// Energy transferred over the series, integrating with the trapezoidal rule.
+    pub fn energy(&self) -> Energy {
+        let mut energy = Energy::default();
+        for i in 1..self.len() {
+            let dt_hours = (self.timestamps[i] - self.timestamps[i - 1]) / 3600.0;
+            let (previous, current) = (&self.values[i - 1], &self.values[i]);
+            energy.active += 0.5 * (previous.active + current.active) * dt_hours;
+            energy.reactive += 0.5 * (previous.reactive + current.reactive) * dt_hours;
+        }
+        energy
+    }
+}
+
+/// Voltage and current phase groups measuring the same circuit component.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowerGroup {
+    pub voltages: PhaseGroup,
+    pub currents: PhaseGroup,
+}
+
+/// Phase A, B and C voltage and current phasors of a [`PowerGroup`] at one time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct GroupPhasors {
+    pub voltages: [Phasor; 3],
+    pub currents: [Phasor; 3],
+}
+
+impl Comtrade {
+    /// Instantaneous power `v * i` in W at every sample, from a voltage channel
+    /// and a current channel (0-indexed positions in `analog_channels`). Only the
+    /// `scaling` of the options is used.
+    pub fn instantaneous_power(
+        &self,
+        voltage: usize,
+        current: usize,
+        options: &PhasorOptions,
+    ) -> Result<TimeSeries<f64>, ComtradeError> {
+        let v = self
+            .analog_channels
+            .get(voltage)
+            .ok_or(ComtradeError::AnalogChannelNotFound(voltage))?;
+        let i = self
+            .analog_channels
+            .get(current)
+            .ok_or(ComtradeError::AnalogChannelNotFound(current))?;
+        let factor = si_scaling(&v.config, "V", options)? * si_scaling(&i.config, "A", options)?;
+
+        Ok(TimeSeries {
+            timestamps: self.timestamps.clone(),
+            values: v
+                .data
+                .iter()
+                .zip(i.data.iter())
+                .map(|(v, i)| v * i * factor)
+                .collect(),
+        })
+    }
+
+    /// Single phase power over time from a phase-to-neutral voltage channel and a
+    /// current channel, using their phasors.
+    pub fn power(
+        &self,
+        voltage: usize,
+        current: usize,
+        options: &PhasorOptions,
+    ) -> Result<TimeSeries<PowerQuantities>, ComtradeError> {
+        let complex = self.complex_power(voltage, current, options)?;
+        Ok(complex.map(|s| PowerQuantities::from_complex_power(*s)))
+    }
+
+    /// Total three-phase power over time from phase-to-neutral voltages and
+    /// currents.
+    pub fn three_phase_power(
+        &self,
+        group: &PowerGroup,
+        options: &PhasorOptions,
+    ) -> Result<TimeSeries<PowerQuantities>, ComtradeError> {
+        let a = self.complex_power(group.voltages.a, group.currents.a, options)?;
+        let b = self.complex_power(group.voltages.b, group.currents.b, options)?;
+        let c = self.complex_power(group.voltages.c, group.currents.c, options)?;
+
+        Ok(TimeSeries {
+            timestamps: a.timestamps,
+            values: a
+                .values
+                .iter()
+                .zip(b.values.iter())
+                .zip(c.values.iter())
+                .map(|((a, b), c)| PowerQuantities::from_complex_power(*a + *b + *c))
+                .collect(),
+        })
+    }
+
+    /// Pair up three-phase voltage and current groups monitoring the same circuit
+    /// component. Voltages are channels in V, kV, etc. and currents in A, kA, etc.
+    pub fn power_groups(&self) -> Vec<PowerGroup> {
+        let groups = self.three_phase_groups();
+        let (voltages, currents): (Vec<&PhaseGroup>, Vec<&PhaseGroup>) = groups
+            .iter()
+            .filter(|g| si_factor(&g.units, "V").is_some() || si_factor(&g.units, "A").is_some())
+            .partition(|g| si_factor(&g.units, "V").is_some());
+
+        let mut used = vec![false; currents.len()];
+        let mut pairs = Vec::new();
+        for voltage_group in voltages {
+            let component = normalise_field(&voltage_group.circuit_component);
+            let matching = currents
+                .iter()
+                .enumerate()
+                .find(|(i, g)| !used[*i] && normalise_field(&g.circuit_component) == component);
+            if let Some((i, current_group)) = matching {
+                used[i] = true;
+                pairs.push(PowerGroup {
+                    voltages: voltage_group.clone(),
+                    currents: (*current_group).clone(),
+                });
+            }
+        }
+        pairs
+    }
+
+    /// Phasors of an analog channel in the SI unit `base` (e.g. V rather than
+    /// kV), in primary or secondary terms according to the options.
+    pub(crate) fn si_phasors(
+        &self,
+        channel: usize,
+        base: &str,
+        options: &PhasorOptions,
+    ) -> Result<TimeSeries<Phasor>, ComtradeError> {
+        let config = &self
+            .analog_channels
+            .get(channel)
+            .ok_or(ComtradeError::AnalogChannelNotFound(channel))?
+            .config;
+        let factor = si_scaling(config, base, options)?;
+
+        // Phasors are calculated in recorded units and scaled to SI here.
+        let unscaled = PhasorOptions {
+            scaling: None,
+            ..options.clone()
+        };
+        Ok(self.phasors(channel, &unscaled)?.map(|p| *p * factor))
+    }
+
+    /// Phasor of an analog channel in the SI unit `base` from the window ending
+    /// at `time`, like [`Comtrade::si_phasors`].
+    pub(crate) fn si_phasor_at(
+        &self,
+        channel: usize,
+        base: &str,
+        time: f64,
+        options: &PhasorOptions,
+    ) -> Result<Option<Phasor>, ComtradeError> {
+        let config = &self
+            .analog_channels
+            .get(channel)
+            .ok_or(ComtradeError::AnalogChannelNotFound(channel))?
+            .config;
+        let factor = si_scaling(config, base, options)?;
+        let unscaled = PhasorOptions {
+            scaling: None,
+            ..options.clone()
+        };
+        Ok(self
+            .phasor_at(channel, time, &unscaled)?
+            .map(|p| p * factor))
+    }
+
+    /// Phase A, B and C voltage and current phasors of a group in V and A from the
+    /// windows ending at `time`. `None` if there isn't a full window of data.
+    pub(crate) fn group_phasors_at(
+        &self,
+        group: &PowerGroup,
+        time: f64,
+        options: &PhasorOptions,
+    ) -> Result<Option<GroupPhasors>, ComtradeError> {
+        let phasor = |channel, base| self.si_phasor_at(channel, base, time, options);
+        let v = [
+            phasor(group.voltages.a, "V")?,
+            phasor(group.voltages.b, "V")?,
+            phasor(group.voltages.c, "V")?,
+        ];
+        let i = [
+            phasor(group.currents.a, "A")?,
+            phasor(group.currents.b, "A")?,
+            phasor(group.currents.c, "A")?,
+        ];
+        match (v, i) {
+            ([Some(va), Some(vb), Some(vc)], [Some(ia), Some(ib), Some(ic)]) => {
+                Ok(Some(GroupPhasors {
+                    voltages: [va, vb, vc],
+                    currents: [ia, ib, ic],
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Complex power `S = V I*` in VA over time.
+    fn complex_power(
+        &self,
+        voltage: usize,
+        current: usize,
+        options: &PhasorOptions,
+    ) -> Result<TimeSeries<Phasor>, ComtradeError> {
+        let v = self.si_phasors(voltage, "V", options)?;
+        let i = self.si_phasors(current, "A", options)?;
+
+        Ok(TimeSeries {
+            timestamps: v.timestamps,
+            values: v
+                .values
+                .iter()
+                .zip(i.values.iter())
+                .map(|(v, i)| *v * i.conj())
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn si_factor_handles_prefixes() {
+        assert_eq!(si_factor("V", "V"), Some(1.0));
+        assert_eq!(si_factor(" kV ", "V"), Some(1e3));
+        assert_eq!(si_factor("kA", "A"), Some(1e3));
+        assert_eq!(si_factor("mA", "A"), Some(1e-3));
+        assert_eq!(si_factor("kV", "A"), None);
+        assert_eq!(si_factor("°", "V"), None);
+        assert_eq!(si_factor("KV", "V"), Some(1e3));
+        assert_eq!(si_factor("kv", "V"), Some(1e3));
+        assert_eq!(si_factor("KA", "A"), Some(1e3));
+        assert_eq!(si_factor("a", "A"), Some(1.0));
+        assert_eq!(si_factor("MV", "V"), Some(1e6));
+        assert_eq!(si_factor("mv", "V"), Some(1e-3));
+        assert_eq!(si_factor("MVA", "V"), None);
+        assert_eq!(si_factor("MVA", "A"), None);
+    }
+
+    #[test]
+    fn power_quantities_from_complex_power() {
+        let power = PowerQuantities::from_complex_power(Phasor::new(3.0, 4.0));
+        assert_eq!(power.apparent, 5.0);
+        assert_eq!(power.power_factor, 0.6);
+
+        let none = PowerQuantities::from_complex_power(Phasor::ZERO);
+        assert_eq!(none.power_factor, 0.0);
+    }
+}
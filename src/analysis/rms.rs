@@ -0,0 +1,187 @@
+use crate::analysis::phasor::interpolate;
+use crate::analysis::TimeSeries;
+use crate::error::ComtradeError;
+use crate::Comtrade;
+
+/// Length of the window used to calculate RMS values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RmsWindow {
+    /// Number of cycles of the record's line frequency.
+    Cycles(f64),
+
+    /// Duration in seconds.
+    Duration(f64),
+}
+
+impl RmsWindow {
+    fn duration(&self, line_frequency: f64) -> f64 {
+        match self {
+            RmsWindow::Cycles(cycles) => cycles / line_frequency,
+            RmsWindow::Duration(duration) => *duration,
+        }
+    }
+}
+
+/// Running integral of the signal squared at each sample, using the trapezoidal
+/// rule. Working with the integral over time rather than summing samples keeps
+/// the window the right length when the sampling rate changes part way through.
+pub(crate) struct SquareIntegral<'a> {
+    timestamps: &'a [f64],
+    cumulative: Vec<f64>,
+}
+
+impl<'a> SquareIntegral<'a> {
+    pub(crate) fn new(timestamps: &'a [f64], data: &[f64]) -> Self {
+        let mut cumulative = Vec::with_capacity(data.len());
+        let mut total = 0.0;
+        for i in 0..data.len() {
+            if i > 0 {
+                let dt = timestamps[i] - timestamps[i - 1];
+                total += 0.5 * (data[i] * data[i] + data[i - 1] * data[i - 1]) * dt;
+            }
+            cumulative.push(total);
+        }
+        SquareIntegral {
+            timestamps,
+            cumulative,
+        }
+    }
+
+    /// RMS value over `start..end`, or `None` if that isn't within the data.
+    pub(crate) fn rms(&self, start: f64, end: f64) -> Option<f64> {
+        let first = *self.timestamps.first()?;
+        let last = *self.timestamps.last()?;
+        if start < first - 1e-12 || end > last + 1e-12 || end <= start {
+            return None;
+        }
+
+        let integral = interpolate(self.timestamps, &self.cumulative, end)
+            - interpolate(self.timestamps, &self.cumulative, start);
+        Some((integral.max(0.0) / (end - start)).sqrt())
+    }
+}
+
+impl Comtrade {
+    pub(crate) fn analog_data(&self, channel: usize) -> Result<&[f64], ComtradeError> {
+        self.analog_channels
+            .get(channel)
+            .map(|c| c.data.as_slice())
+            .ok_or(ComtradeError::AnalogChannelNotFound(channel))
+    }
+
+    /// True RMS of an analog channel over a window sliding along the record a
+    /// sample at a time. There is a value for every sample from the first one
+    /// with a full window of data before it. Values are in the units the channel
+    /// was recorded in; use [`crate::AnalogConfig::scaling_factor`] to convert.
+    pub fn rms(&self, channel: usize, window: RmsWindow) -> Result<TimeSeries<f64>, ComtradeError> {
+        if let RmsWindow::Cycles(_) = window {
+            self.check_line_frequency()?;
+        }
+        let integral = SquareIntegral::new(&self.timestamps, self.analog_data(channel)?);
+        let width = window.duration(self.line_frequency);
+
+        let mut series = TimeSeries::new();
+        for t in self.timestamps.iter() {
+            if let Some(rms) = integral.rms(t - width, *t) {
+                series.push(*t, rms);
+            }
+        }
+        Ok(series)
+    }
+
+    /// True RMS of an analog channel over a window of `cycles` cycles of the
+    /// tracked `frequency` (e.g. from [`Comtrade::frequency`]), sliding along the
+    /// record a sample at a time. The line frequency is used until the first
+    /// frequency estimate.
+    pub fn rms_tracking(
+        &self,
+        channel: usize,
+        cycles: f64,
+        frequency: &TimeSeries<f64>,
+    ) -> Result<TimeSeries<f64>, ComtradeError> {
+        self.check_line_frequency()?;
+        let integral = SquareIntegral::new(&self.timestamps, self.analog_data(channel)?);
+
+        let mut series = TimeSeries::new();
+        for t in self.timestamps.iter() {
+            let width = cycles / self.tracked_frequency_at(frequency, *t);
+            if let Some(rms) = integral.rms(t - width, *t) {
+                series.push(*t, rms);
+            }
+        }
+        Ok(series)
+    }
+
+    /// RMS over consecutive, non-overlapping cycles of the line frequency. Each
+    /// value is timestamped at the end of its cycle.
+    pub fn cycle_rms(&self, channel: usize) -> Result<TimeSeries<f64>, ComtradeError> {
+        self.stepped_rms(channel, 1.0)
+    }
+
+    /// One cycle RMS refreshed every half cycle, as used for voltage dip and swell
+    /// evaluation in IEC 61000-4-30 (U<sub>rms(1/2)</sub>). Each value is
+    /// timestamped at the end of its window.
+    pub fn half_cycle_rms(&self, channel: usize) -> Result<TimeSeries<f64>, ComtradeError> {
+        self.stepped_rms(channel, 0.5)
+    }
+
+    fn stepped_rms(
+        &self,
+        channel: usize,
+        step_cycles: f64,
+    ) -> Result<TimeSeries<f64>, ComtradeError> {
+        self.check_line_frequency()?;
+        let integral = SquareIntegral::new(&self.timestamps, self.analog_data(channel)?);
+        let width = 1.0 / self.line_frequency;
+        let step = step_cycles * width;
+
+        let mut series = TimeSeries::new();
+        let start = match self.timestamps.first() {
+            Some(start) => *start,
+            None => return Ok(series),
+        };
+
+        let mut n = 0;
+        loop {
+            let end = start + width + n as f64 * step;
+            match integral.rms(end - width, end) {
+                Some(rms) => series.push(end, rms),
+                None => break,
+            }
+            n += 1;
+        }
+        Ok(series)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{PI, SQRT_2};
+
+    #[test]
+    fn rms_of_sine_across_sampling_rate_change() {
+        // 0.1s at 960Hz followed by 0.1s at 4000Hz.
+        let mut timestamps: Vec<f64> = (0..96).map(|i| i as f64 / 960.0).collect();
+        timestamps.extend((0..400).map(|i| 0.1 + i as f64 / 4000.0));
+        let data: Vec<f64> = timestamps
+            .iter()
+            .map(|t| 7.0 * SQRT_2 * (2.0 * PI * 60.0 * t).sin())
+            .collect();
+
+        let integral = SquareIntegral::new(&timestamps, &data);
+        for end in [1.0 / 60.0, 0.05, 0.1, 0.105, 0.15] {
+            let rms = integral.rms(end - 1.0 / 60.0, end).unwrap();
+            assert!((rms - 7.0).abs() < 0.05, "RMS at {} was {}", end, rms);
+        }
+        assert!(integral.rms(-0.01, 0.01).is_none());
+    }
+
+    #[test]
+    fn rms_of_dc() {
+        let timestamps: Vec<f64> = (0..100).map(|i| i as f64 * 0.001).collect();
+        let data = vec![-3.0; 100];
+        let integral = SquareIntegral::new(&timestamps, &data);
+        assert!((integral.rms(0.0123, 0.0456).unwrap() - 3.0).abs() < 1e-9);
+    }
+}
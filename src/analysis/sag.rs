@@ -0,0 +1,388 @@
+use std::f64::consts::PI;
+
+use crate::analysis::phasor::interpolate;
+use crate::analysis::{PhaseGroup, Phasor, PhasorOptions, SequenceComponents};
+use crate::error::ComtradeError;
+use crate::Comtrade;
+
+/// Zero or negative sequence smaller than this fraction of the positive sequence
+/// counts as absent when classifying sags.
+const UNBALANCE_THRESHOLD: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoltageEventKind {
+    Sag,
+    Swell,
+}
+
+/// Type of a three-phase sag in the ABC classification (Bollen, "Understanding
+/// Power Quality Problems"), from the phase voltages at its deepest point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SagType {
+    /// All three phases drop equally, e.g. a three-phase fault.
+    A,
+    /// One phase drops, with zero sequence, e.g. a single phase to ground fault.
+    B,
+    /// Two phases drop, without zero sequence, e.g. a phase to phase fault.
+    C,
+    /// One phase drops a lot and the other two a little, without zero sequence,
+    /// e.g. a phase to phase fault seen through a delta-star transformer.
+    D,
+    /// Two or more phases drop unequally, with zero sequence, e.g. a two phase to
+    /// ground fault.
+    E,
+}
+
+impl SagType {
+    /// Classify from phase voltages in per unit of their references.
+    fn classify(phases: [Phasor; 3], sag_threshold: f64) -> Self {
+        let components = SequenceComponents::from_phases(phases[0], phases[1], phases[2]);
+        let positive = components.positive.magnitude();
+        let negative = components.negative.magnitude();
+        let zero = components.zero.magnitude();
+
+        if zero >= UNBALANCE_THRESHOLD * positive {
+            let dropped = phases
+                .iter()
+                .filter(|p| p.magnitude() < sag_threshold)
+                .count();
+            return if dropped <= 1 { SagType::B } else { SagType::E };
+        }
+        if negative < UNBALANCE_THRESHOLD * positive {
+            return SagType::A;
+        }
+
+        let mut magnitudes = phases.map(|p| p.magnitude());
+        magnitudes.sort_by(f64::total_cmp);
+        let [lowest, middle, highest] = magnitudes;
+        if middle - lowest > highest - middle {
+            SagType::D
+        } else {
+            SagType::C
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SagSwellOptions {
+    /// Declared phase to neutral RMS voltage in the units the channels were
+    /// recorded in. `None` uses the RMS of each phase over the first cycle of the
+    /// record, which should be before any event.
+    pub reference: Option<f64>,
+
+    /// A sag starts when any phase falls below this fraction of its reference.
+    pub sag_threshold: f64,
+
+    /// A swell starts when any phase rises above this fraction of its reference.
+    pub swell_threshold: f64,
+
+    /// Events end when every phase is back past the threshold by this many per
+    /// unit.
+    pub hysteresis: f64,
+
+    /// Change from the previous cycle, in per unit of the reference peak, which
+    /// marks the instant an event starts or ends on the waveform.
+    pub point_on_wave_threshold: f64,
+
+    /// Used for the phasors which classify sags.
+    pub phasor: PhasorOptions,
+}
+
+impl Default for SagSwellOptions {
+    fn default() -> Self {
+        SagSwellOptions {
+            reference: None,
+            sag_threshold: 0.9,
+            swell_threshold: 1.1,
+            hysteresis: 0.02,
+            point_on_wave_threshold: 0.05,
+            phasor: PhasorOptions::default(),
+        }
+    }
+}
+
+/// Voltage sag (dip) or swell across a three-phase group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoltageEvent {
+    pub kind: VoltageEventKind,
+
+    /// Times in seconds relative to the start of the record when the first phase
+    /// crossed the threshold and when the last phase recovered, from the half
+    /// cycle RMS as in IEC 61000-4-30. These lag the waveform by up to a cycle.
+    pub start: f64,
+    pub end: f64,
+
+    /// Times the event starts and ends on the waveform, found from the change in
+    /// each phase compared with a cycle earlier.
+    pub inception: f64,
+    pub recovery: f64,
+
+    /// Points on wave of the inception and recovery in degrees, measured from a
+    /// positive going zero crossing of the most affected phase before the event.
+    pub inception_point_on_wave: f64,
+    pub recovery_point_on_wave: f64,
+
+    /// Lowest (for a sag) or highest (for a swell) half cycle RMS of each phase
+    /// during the event, in per unit of its reference.
+    pub phase_magnitudes: [f64; 3],
+
+    /// Whether phases A, B and C crossed the threshold.
+    pub phases: [bool; 3],
+
+    /// Sag type at the deepest point. `None` for swells.
+    pub sag_type: Option<SagType>,
+}
+
+impl VoltageEvent {
+    pub fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+
+    /// Residual voltage of a sag or peak voltage of a swell in per unit: the most
+    /// extreme of the phase magnitudes.
+    pub fn magnitude(&self) -> f64 {
+        match self.kind {
+            VoltageEventKind::Sag => self
+                .phase_magnitudes
+                .iter()
+                .copied()
+                .fold(f64::MAX, f64::min),
+            VoltageEventKind::Swell => self.phase_magnitudes.iter().copied().fold(0.0, f64::max),
+        }
+    }
+
+    /// Difference between the reference and the magnitude in per unit.
+    pub fn depth(&self) -> f64 {
+        (1.0 - self.magnitude()).abs()
+    }
+
+    /// Most affected phase, 0 to 2 for A to C.
+    fn worst_phase(&self) -> usize {
+        let deviation = |p: usize| (1.0 - self.phase_magnitudes[p]).abs();
+        (0..3)
+            .max_by(|a, b| deviation(*a).total_cmp(&deviation(*b)))
+            .unwrap()
+    }
+}
+
+impl Comtrade {
+    /// Find voltage sags and swells in a group of phase to neutral voltages from
+    /// their half cycle RMS. Returns no events if a reference voltage isn't
+    /// positive or the record is too short to work one out.
+    pub fn voltage_events(
+        &self,
+        group: &PhaseGroup,
+        options: &SagSwellOptions,
+    ) -> Result<Vec<VoltageEvent>, ComtradeError> {
+        self.check_line_frequency()?;
+        let channels = [group.a, group.b, group.c];
+        let cycle = 1.0 / self.line_frequency;
+
+        let mut references = [0.0; 3];
+        let mut rms = Vec::new();
+        for (phase, channel) in channels.iter().enumerate() {
+            let series = self.half_cycle_rms(*channel)?;
+            references[phase] = match options.reference {
+                Some(reference) => reference,
+                None => match series.values.first() {
+                    Some(first) => *first,
+                    None => return Ok(Vec::new()),
+                },
+            };
+            rms.push(series);
+        }
+        if references.iter().any(|r| r.is_nan() || *r <= 0.0) {
+            return Ok(Vec::new());
+        }
+
+        let timestamps = &rms[0].timestamps;
+        let per_unit =
+            |n: usize| -> [f64; 3] { [0, 1, 2].map(|p| rms[p].values[n] / references[p]) };
+
+        let mut events = Vec::new();
+        for kind in [VoltageEventKind::Sag, VoltageEventKind::Swell] {
+            type Check = fn(f64, &SagSwellOptions) -> bool;
+            let (outside, back): (Check, Check) = match kind {
+                VoltageEventKind::Sag => (
+                    |v: f64, o: &SagSwellOptions| v < o.sag_threshold,
+                    |v: f64, o: &SagSwellOptions| v >= o.sag_threshold + o.hysteresis,
+                ),
+                VoltageEventKind::Swell => (
+                    |v: f64, o: &SagSwellOptions| v > o.swell_threshold,
+                    |v: f64, o: &SagSwellOptions| v <= o.swell_threshold - o.hysteresis,
+                ),
+            };
+
+            let mut n = 0;
+            while n < timestamps.len() {
+                if !per_unit(n).iter().any(|v| outside(*v, options)) {
+                    n += 1;
+                    continue;
+                }
+
+                let start = n;
+                let mut extremes = per_unit(n);
+                let mut phases = [false; 3];
+                let mut deepest = (n, 0.0);
+                while n < timestamps.len() {
+                    let values = per_unit(n);
+                    if values.iter().all(|v| back(*v, options)) {
+                        break;
+                    }
+                    for p in 0..3 {
+                        phases[p] |= outside(values[p], options);
+                        extremes[p] = match kind {
+                            VoltageEventKind::Sag => extremes[p].min(values[p]),
+                            VoltageEventKind::Swell => extremes[p].max(values[p]),
+                        };
+                        let deviation = (1.0 - values[p]).abs();
+                        if deviation > deepest.1 {
+                            deepest = (n, deviation);
+                        }
+                    }
+                    n += 1;
+                }
+                let end = n.min(timestamps.len() - 1);
+
+                let mut event = VoltageEvent {
+                    kind,
+                    start: timestamps[start],
+                    end: timestamps[end],
+                    inception: timestamps[start],
+                    recovery: timestamps[end],
+                    inception_point_on_wave: 0.0,
+                    recovery_point_on_wave: 0.0,
+                    phase_magnitudes: extremes,
+                    phases,
+                    sag_type: None,
+                };
+
+                let level = options.point_on_wave_threshold * std::f64::consts::SQRT_2;
+                let inception = self.first_deviation(
+                    &channels,
+                    &references,
+                    level,
+                    event.start - cycle,
+                    event.start,
+                )?;
+                event.inception = inception.unwrap_or(event.start);
+                let recovery = self.first_deviation(
+                    &channels,
+                    &references,
+                    level,
+                    (event.inception + cycle).max(event.end - cycle),
+                    event.end,
+                )?;
+                event.recovery = recovery.unwrap_or(event.end);
+
+                let worst = event.worst_phase();
+                if let Some(pre_event) =
+                    self.phasor_at(channels[worst], event.inception, &options.phasor)?
+                {
+                    event.inception_point_on_wave = self.point_on_wave(pre_event, event.inception);
+                    event.recovery_point_on_wave = self.point_on_wave(pre_event, event.recovery);
+                }
+
+                if kind == VoltageEventKind::Sag {
+                    let time = timestamps[deepest.0];
+                    let phasors = [
+                        self.phasor_at(channels[0], time, &options.phasor)?,
+                        self.phasor_at(channels[1], time, &options.phasor)?,
+                        self.phasor_at(channels[2], time, &options.phasor)?,
+                    ];
+                    if let [Some(a), Some(b), Some(c)] = phasors {
+                        event.sag_type = Some(SagType::classify(
+                            [a / references[0], b / references[1], c / references[2]],
+                            options.sag_threshold,
+                        ));
+                    }
+                }
+
+                events.push(event);
+            }
+        }
+
+        events.sort_by(|a, b| a.start.total_cmp(&b.start));
+        Ok(events)
+    }
+
+    /// First time in `from..=to` when any of the channels differs from its value
+    /// a cycle earlier by more than `level` times its reference.
+    fn first_deviation(
+        &self,
+        channels: &[usize; 3],
+        references: &[f64; 3],
+        level: f64,
+        from: f64,
+        to: f64,
+    ) -> Result<Option<f64>, ComtradeError> {
+        let first = match self.timestamps.first() {
+            Some(first) => *first,
+            None => return Ok(None),
+        };
+        let cycle = 1.0 / self.line_frequency;
+        let from = from.max(first + cycle);
+        let start = self.timestamps.partition_point(|t| *t < from);
+        let end = self.timestamps.partition_point(|t| *t <= to);
+
+        let data = channels
+            .iter()
+            .map(|c| self.analog_data(*c))
+            .collect::<Result<Vec<_>, _>>()?;
+        for n in start..end {
+            let time = self.timestamps[n];
+            let deviates = (0..3).any(|p| {
+                let previous = interpolate(&self.timestamps, data[p], time - cycle);
+                (data[p][n] - previous).abs() > level * references[p]
+            });
+            if deviates {
+                return Ok(Some(time));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Angle in degrees through the cycle of the waveform with the given phasor at
+    /// `time`, from 0 at a positive going zero crossing.
+    fn point_on_wave(&self, phasor: Phasor, time: f64) -> f64 {
+        // The phasor's waveform is a cosine, which crosses zero going positive a
+        // quarter cycle before its peak.
+        let angle = 2.0 * PI * self.line_frequency * time + phasor.angle() + PI / 2.0;
+        angle.to_degrees().rem_euclid(360.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phasor(magnitude: f64, angle_degrees: f64) -> Phasor {
+        Phasor::from_polar(magnitude, angle_degrees.to_radians())
+    }
+
+    #[test]
+    fn sags_are_classified_by_type() {
+        let balanced = |m: f64| [phasor(m, 0.0), phasor(m, -120.0), phasor(m, 120.0)];
+        assert_eq!(SagType::classify(balanced(0.5), 0.9), SagType::A);
+
+        let single_phase = [phasor(0.5, 0.0), phasor(1.0, -120.0), phasor(1.0, 120.0)];
+        assert_eq!(SagType::classify(single_phase, 0.9), SagType::B);
+
+        // Phase to phase fault, and the same seen through a delta-star transformer.
+        let type_c = [
+            Phasor::new(1.0, 0.0),
+            Phasor::new(-0.5, -0.25 * 3f64.sqrt()),
+            Phasor::new(-0.5, 0.25 * 3f64.sqrt()),
+        ];
+        assert_eq!(SagType::classify(type_c, 0.9), SagType::C);
+        let type_d = [
+            Phasor::new(0.5, 0.0),
+            Phasor::new(-0.25, -0.5 * 3f64.sqrt()),
+            Phasor::new(-0.25, 0.5 * 3f64.sqrt()),
+        ];
+        assert_eq!(SagType::classify(type_d, 0.9), SagType::D);
+
+        let two_phase = [phasor(1.0, 0.0), phasor(0.3, -120.0), phasor(0.6, 120.0)];
+        assert_eq!(SagType::classify(two_phase, 0.9), SagType::E);
+    }
+}
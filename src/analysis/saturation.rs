@@ -0,0 +1,93 @@
+use std::f64::consts::PI;
+
+use crate::analysis::inrush::detection_intervals;
+use crate::analysis::DetectionInterval;
+use crate::error::ComtradeError;
+use crate::Comtrade;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaturationOptions {
+    /// Windows whose peak absolute value is below this, in the units the channel
+    /// was recorded in, are ignored. Set it above the noise level.
+    pub pickup: f64,
+
+    /// Rate of change, as a multiple of the largest rate of change of a sine wave
+    /// with the window's peak value, above which the current is treated as
+    /// collapsing. Needs at least 16 or so samples per cycle to tell apart from
+    /// the rate of change of an undistorted wave.
+    pub max_slope: f64,
+
+    /// Rate of change, as a fraction of the largest rate of change of a sine wave
+    /// with the window's peak value, below which the waveform counts as flat.
+    pub flat_slope: f64,
+
+    /// Length of flat region, in degrees of the line frequency, at or above which
+    /// a window looks saturated. A sine wave is flat for around 11° at its peaks.
+    pub min_flat_degrees: f64,
+}
+
+impl Default for SaturationOptions {
+    fn default() -> Self {
+        SaturationOptions {
+            pickup: 0.0,
+            max_slope: 2.0,
+            flat_slope: 0.1,
+            min_flat_degrees: 30.0,
+        }
+    }
+}
+
+impl Comtrade {
+    /// Find CT saturation in a current channel, from one cycle windows stepped
+    /// along by half a cycle. A saturating CT's output follows the primary current
+    /// then collapses abruptly and stays flat until the primary current reverses.
+    /// A window looks saturated if its rate of change jumps beyond what a sine
+    /// wave of the same peak could manage, or it has long flat regions, and each
+    /// of these counts for half of the confidence. Inrush can also have flat
+    /// regions, see [`Comtrade::detect_inrush`].
+    pub fn detect_ct_saturation(
+        &self,
+        channel: usize,
+        options: &SaturationOptions,
+    ) -> Result<Vec<DetectionInterval>, ComtradeError> {
+        self.check_line_frequency()?;
+        let data = self.analog_data(channel)?;
+        let omega = 2.0 * PI * self.line_frequency;
+
+        let mut windows = Vec::new();
+        for (start, end) in self.half_cycle_steps() {
+            let lo = self.timestamps.partition_point(|t| *t < start);
+            let hi = self.timestamps.partition_point(|t| *t <= end);
+            let peak = data[lo..hi].iter().fold(0.0, |peak, x| x.abs().max(peak));
+            if peak <= options.pickup || peak == 0.0 {
+                windows.push((start, end, 0.0));
+                continue;
+            }
+            let sine_slope = omega * peak;
+
+            let mut steep = false;
+            let mut flat = 0.0;
+            let mut longest: f64 = 0.0;
+            for n in lo + 1..hi {
+                let dt = self.timestamps[n] - self.timestamps[n - 1];
+                if dt <= 0.0 {
+                    continue;
+                }
+                let slope = ((data[n] - data[n - 1]) / dt).abs();
+                steep |= slope > options.max_slope * sine_slope;
+                if slope < options.flat_slope * sine_slope {
+                    flat += dt;
+                    longest = longest.max(flat);
+                } else {
+                    flat = 0.0;
+                }
+            }
+            let flat = longest * self.line_frequency * 360.0 >= options.min_flat_degrees;
+
+            let score = [steep, flat].iter().filter(|c| **c).count() as f64 / 2.0;
+            windows.push((start, end, score));
+        }
+
+        Ok(detection_intervals(&windows))
+    }
+}
@@ -0,0 +1,186 @@
+use std::f64::consts::PI;
+
+use crate::analysis::{Phasor, PhasorOptions, TimeSeries};
+use crate::error::ComtradeError;
+use crate::query::normalise_field;
+use crate::Comtrade;
+
+/// Three analog channels measuring the phases of the same circuit component.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseGroup {
+    pub circuit_component: String,
+    pub units: String,
+
+    /// 0-indexed position of the phase A channel in `analog_channels`.
+    pub a: usize,
+    /// 0-indexed position of the phase B channel in `analog_channels`.
+    pub b: usize,
+    /// 0-indexed position of the phase C channel in `analog_channels`.
+    pub c: usize,
+}
+
+/// One of the symmetrical components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sequence {
+    Zero,
+    Positive,
+    Negative,
+}
+
+/// Zero, positive and negative sequence phasors of a three-phase quantity.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SequenceComponents {
+    pub zero: Phasor,
+    pub positive: Phasor,
+    pub negative: Phasor,
+}
+
+impl SequenceComponents {
+    /// Resolve three phase phasors into their symmetrical components.
+    pub fn from_phases(a: Phasor, b: Phasor, c: Phasor) -> Self {
+        let alpha = Phasor::from_polar(1.0, 2.0 * PI / 3.0);
+        let alpha_squared = alpha * alpha;
+        SequenceComponents {
+            zero: (a + b + c) / 3.0,
+            positive: (a + alpha * b + alpha_squared * c) / 3.0,
+            negative: (a + alpha_squared * b + alpha * c) / 3.0,
+        }
+    }
+
+    pub fn get(&self, sequence: Sequence) -> Phasor {
+        match sequence {
+            Sequence::Zero => self.zero,
+            Sequence::Positive => self.positive,
+            Sequence::Negative => self.negative,
+        }
+    }
+
+    /// Convert back into the three phase phasors `(a, b, c)`.
+    pub fn to_phases(&self) -> (Phasor, Phasor, Phasor) {
+        let alpha = Phasor::from_polar(1.0, 2.0 * PI / 3.0);
+        let alpha_squared = alpha * alpha;
+        (
+            self.zero + self.positive + self.negative,
+            self.zero + alpha_squared * self.positive + alpha * self.negative,
+            self.zero + alpha * self.positive + alpha_squared * self.negative,
+        )
+    }
+}
+
+impl Comtrade {
+    /// Group analog channels into sets of phases A, B and C measuring the same
+    /// circuit component in the same units, using the channels' phase field. If a
+    /// component has more than one channel for a phase, the first A, B and C make
+    /// up the first group, the second of each the second group and so on.
+    pub fn three_phase_groups(&self) -> Vec<PhaseGroup> {
+        // Keep groups in the order their components first appear in the record.
+        let mut keys: Vec<(String, String)> = Vec::new();
+        let mut phases: Vec<[Vec<usize>; 3]> = Vec::new();
+
+        for (i, channel) in self.analog_channels.iter().enumerate() {
+            let phase = match normalise_field(&channel.config.phase).as_str() {
+                "a" => 0,
+                "b" => 1,
+                "c" => 2,
+                _ => continue,
+            };
+            let key = (
+                normalise_field(&channel.config.circuit_component_being_monitored),
+                channel.config.units.trim().to_string(),
+            );
+            let group = match keys.iter().position(|k| *k == key) {
+                Some(group) => group,
+                None => {
+                    keys.push(key);
+                    phases.push(Default::default());
+                    keys.len() - 1
+                }
+            };
+            phases[group][phase].push(i);
+        }
+
+        let mut groups = Vec::new();
+        for ([a, b, c], _) in phases.iter().zip(keys.iter()) {
+            for ((a, b), c) in a.iter().zip(b.iter()).zip(c.iter()) {
+                let config = &self.analog_channels[*a].config;
+                groups.push(PhaseGroup {
+                    circuit_component: config.circuit_component_being_monitored.trim().to_string(),
+                    units: config.units.trim().to_string(),
+                    a: *a,
+                    b: *b,
+                    c: *c,
+                });
+            }
+        }
+        groups
+    }
+
+    /// Symmetrical components of a group of phases over time, from the phasors of
+    /// each phase calculated with the given options.
+    pub fn sequence_components(
+        &self,
+        group: &PhaseGroup,
+        options: &PhasorOptions,
+    ) -> Result<TimeSeries<SequenceComponents>, ComtradeError> {
+        let a = self.phasors(group.a, options)?;
+        let b = self.phasors(group.b, options)?;
+        let c = self.phasors(group.c, options)?;
+
+        Ok(TimeSeries {
+            timestamps: a.timestamps,
+            values: a
+                .values
+                .iter()
+                .zip(b.values.iter())
+                .zip(c.values.iter())
+                .map(|((a, b), c)| SequenceComponents::from_phases(*a, *b, *c))
+                .collect(),
+        })
+    }
+
+    /// Symmetrical components over time for every group of phases found by
+    /// [`Comtrade::three_phase_groups`].
+    pub fn all_sequence_components(
+        &self,
+        options: &PhasorOptions,
+    ) -> Result<Vec<(PhaseGroup, TimeSeries<SequenceComponents>)>, ComtradeError> {
+        self.three_phase_groups()
+            .into_iter()
+            .map(|group| {
+                let components = self.sequence_components(&group, options)?;
+                Ok((group, components))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Phasor, b: Phasor) {
+        assert!((a - b).magnitude() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn balanced_phases_are_all_positive_sequence() {
+        let a = Phasor::from_polar(10.0, 0.3);
+        let b = a.rotate(-2.0 * PI / 3.0);
+        let c = a.rotate(2.0 * PI / 3.0);
+        let components = SequenceComponents::from_phases(a, b, c);
+        assert_close(components.positive, a);
+        assert_close(components.negative, Phasor::ZERO);
+        assert_close(components.zero, Phasor::ZERO);
+    }
+
+    #[test]
+    fn sequence_components_round_trip() {
+        let a = Phasor::new(1.0, 2.0);
+        let b = Phasor::new(-3.0, 0.5);
+        let c = Phasor::new(0.25, -1.0);
+        let (a2, b2, c2) = SequenceComponents::from_phases(a, b, c).to_phases();
+        assert_close(a, a2);
+        assert_close(b, b2);
+        assert_close(c, c2);
+    }
+}
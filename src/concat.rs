@@ -0,0 +1,182 @@
+use crate::error::ComtradeError;
+use crate::merge::{utc_start_time, worst_time_quality};
+use crate::query::normalise_field;
+use crate::window::duration_to_seconds;
+use crate::{AnalogConfig, Comtrade, SamplingRate, StatusConfig};
+
+/// Whether two analog channels record the same thing. The data scaling and
+/// limits are allowed to differ as they are specific to each record's data.
+fn analog_configs_match(a: &AnalogConfig, b: &AnalogConfig) -> bool {
+    normalise_field(&a.name) == normalise_field(&b.name)
+        && normalise_field(&a.phase) == normalise_field(&b.phase)
+        && normalise_field(&a.circuit_component_being_monitored)
+            == normalise_field(&b.circuit_component_being_monitored)
+        && a.units.trim() == b.units.trim()
+        && a.primary_factor == b.primary_factor
+        && a.secondary_factor == b.secondary_factor
+        && a.scaling_mode == b.scaling_mode
+}
+
+fn status_configs_match(a: &StatusConfig, b: &StatusConfig) -> bool {
+    normalise_field(&a.name) == normalise_field(&b.name)
+        && normalise_field(&a.phase) == normalise_field(&b.phase)
+        && normalise_field(&a.circuit_component_being_monitored)
+            == normalise_field(&b.circuit_component_being_monitored)
+        && a.normal_status_value == b.normal_status_value
+}
+
+/// Interval in seconds between the first two samples of a record.
+fn first_sample_interval(record: &Comtrade) -> Option<f64> {
+    match record.sampling_rates.first() {
+        Some(rate) => Some(1.0 / rate.rate_hz),
+        None => match record.timestamps.as_slice() {
+            [first, second, ..] => Some(second - first),
+            _ => None,
+        },
+    }
+}
+
+/// Join sampling rate segments end to end, combining adjacent segments with the
+/// same rate into one.
+fn append_sampling_rates(rates: &mut Vec<SamplingRate>, other: &[SamplingRate], num_samples: u32) {
+    for rate in other {
+        let end_sample_number = rate.end_sample_number + num_samples;
+        match rates.last_mut() {
+            Some(last) if last.rate_hz == rate.rate_hz => {
+                last.end_sample_number = end_sample_number
+            }
+            _ => rates.push(SamplingRate {
+                rate_hz: rate.rate_hz,
+                end_sample_number,
+            }),
+        }
+    }
+}
+
+impl Comtrade {
+    /// Append a record which carries straight on from this one, as produced by
+    /// continuous recorders which split long captures across several files.
+    ///
+    /// The records must have the same channels and the other record must start
+    /// one sample interval after this one ends (to within half a sample). Samples
+    /// are renumbered to carry on from this record and sampling rate segments are
+    /// joined, giving several segments where the rates differ. If either record
+    /// relies on critical timestamps rather than sampling rates, the result does
+    /// too. The trigger time of this record is kept.
+    pub fn append(&mut self, other: &Comtrade) -> Result<(), ComtradeError> {
+        if self.line_frequency != other.line_frequency {
+            return Err(ComtradeError::MismatchedLineFrequency(
+                self.line_frequency,
+                other.line_frequency,
+            ));
+        }
+        if self.analog_channels.len() != other.analog_channels.len()
+            || self.status_channels.len() != other.status_channels.len()
+        {
+            return Err(ComtradeError::MismatchedChannelCount);
+        }
+        if let Some(i) = self
+            .analog_channels
+            .iter()
+            .zip(other.analog_channels.iter())
+            .position(|(a, b)| !analog_configs_match(&a.config, &b.config))
+        {
+            return Err(ComtradeError::MismatchedAnalogChannel(i));
+        }
+        if let Some(i) = self
+            .status_channels
+            .iter()
+            .zip(other.status_channels.iter())
+            .position(|(a, b)| !status_configs_match(&a.config, &b.config))
+        {
+            return Err(ComtradeError::MismatchedStatusChannel(i));
+        }
+
+        if other.timestamps.is_empty() {
+            return Ok(());
+        }
+
+        // Start of the other record relative to the start of this one.
+        let offset = duration_to_seconds(utc_start_time(other) - utc_start_time(self));
+        if let (Some(last), Some(interval)) = (self.timestamps.last(), first_sample_interval(other))
+        {
+            let gap = offset + other.timestamps[0] - last - interval;
+            if gap.abs() > interval / 2.0 {
+                return Err(ComtradeError::NotContiguous(gap));
+            }
+        }
+
+        let num_samples = self.sample_numbers.len() as u32;
+        self.sample_numbers
+            .extend((1..=other.sample_numbers.len() as u32).map(|n| n + num_samples));
+        self.timestamps
+            .extend(other.timestamps.iter().map(|t| t + offset));
+
+        for (channel, other_channel) in self
+            .analog_channels
+            .iter_mut()
+            .zip(other.analog_channels.iter())
+        {
+            channel.data.extend_from_slice(&other_channel.data);
+            channel.recalculate_limits(&self.data_format);
+        }
+        for (channel, other_channel) in self
+            .status_channels
+            .iter_mut()
+            .zip(other.status_channels.iter())
+        {
+            channel.data.extend_from_slice(&other_channel.data);
+        }
+
+        if self.sampling_rates.is_empty() || other.sampling_rates.is_empty() {
+            self.sampling_rates.clear();
+        } else {
+            append_sampling_rates(&mut self.sampling_rates, &other.sampling_rates, num_samples);
+        }
+
+        self.time_quality =
+            worst_time_quality([&self.time_quality, &other.time_quality].into_iter());
+
+        Ok(())
+    }
+
+    /// Join consecutive records into one. See [`Comtrade::append`] for the
+    /// requirements on the records.
+    pub fn concat(records: &[Comtrade]) -> Result<Comtrade, ComtradeError> {
+        let (first, rest) = records.split_first().ok_or(ComtradeError::NoRecords)?;
+        let mut joined = first.clone();
+        for record in rest {
+            joined.append(record)?;
+        }
+        Ok(joined)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(rate_hz: f64, end_sample_number: u32) -> SamplingRate {
+        SamplingRate {
+            rate_hz,
+            end_sample_number,
+        }
+    }
+
+    #[test]
+    fn append_sampling_rates_joins_matching_rates() {
+        let mut rates = vec![rate(1000.0, 100)];
+        append_sampling_rates(&mut rates, &[rate(1000.0, 50)], 100);
+        assert_eq!(rates, vec![rate(1000.0, 150)]);
+    }
+
+    #[test]
+    fn append_sampling_rates_adds_segments_for_different_rates() {
+        let mut rates = vec![rate(1000.0, 100)];
+        append_sampling_rates(&mut rates, &[rate(500.0, 50), rate(1000.0, 60)], 100);
+        assert_eq!(
+            rates,
+            vec![rate(1000.0, 100), rate(500.0, 150), rate(1000.0, 160)]
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use crate::error::ComtradeError;
+use crate::query::field_matches;
+use crate::{AnalogChannel, AnalogConfig, AnalogScalingMode, Comtrade, DataFormat};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Number(f64),
+    /// 0-indexed position in `analog_channels`.
+    Channel(usize),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+}
+
+/// Recursive descent parser for channel expressions:
+///
+/// ```text
+/// expression = term (("+" | "-") term)*
+/// term       = factor (("*" | "/") factor)*
+/// factor     = "-" factor | number | channel | "(" expression ")"
+/// channel    = identifier | "[" any characters except "]" "]"
+/// ```
+struct Parser<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    record: &'a Comtrade,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&mut self, message: &str) -> ComtradeError {
+        let position = self.chars.peek().map_or(self.source.len(), |(i, _)| *i);
+        ComtradeError::InvalidExpression {
+            position,
+            message: message.to_string(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn parse(mut self) -> Result<Expression, ComtradeError> {
+        let expression = self.expression()?;
+        match self.peek() {
+            None => Ok(expression),
+            Some(_) => Err(self.error("unexpected character")),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expression, ComtradeError> {
+        let mut left = self.term()?;
+        loop {
+            let operator = match self.peek() {
+                Some('+') => Operator::Add,
+                Some('-') => Operator::Subtract,
+                _ => return Ok(left),
+            };
+            self.chars.next();
+            let right = self.term()?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expression, ComtradeError> {
+        let mut left = self.factor()?;
+        loop {
+            let operator = match self.peek() {
+                Some('*') => Operator::Multiply,
+                Some('/') => Operator::Divide,
+                _ => return Ok(left),
+            };
+            self.chars.next();
+            let right = self.factor()?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn factor(&mut self) -> Result<Expression, ComtradeError> {
+        match self.peek() {
+            Some('-') => {
+                self.chars.next();
+                Ok(Expression::Negate(Box::new(self.factor()?)))
+            }
+            Some('(') => {
+                self.chars.next();
+                let expression = self.expression()?;
+                match self.peek() {
+                    Some(')') => {
+                        self.chars.next();
+                        Ok(expression)
+                    }
+                    _ => Err(self.error("expected ')'")),
+                }
+            }
+            Some('[') => {
+                self.chars.next();
+                let mut name = String::new();
+                loop {
+                    match self.chars.next() {
+                        Some((_, ']')) => break,
+                        Some((_, c)) => name.push(c),
+                        None => return Err(self.error("expected ']'")),
+                    }
+                }
+                self.channel(&name)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let mut number = String::new();
+                while let Some((_, c)) =
+                    self.chars.next_if(|(_, c)| c.is_ascii_digit() || *c == '.')
+                {
+                    number.push(c);
+                }
+                number
+                    .parse()
+                    .map(Expression::Number)
+                    .map_err(|_| self.error("invalid number"))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let mut name = String::new();
+                while let Some((_, c)) = self
+                    .chars
+                    .next_if(|(_, c)| c.is_alphanumeric() || *c == '_')
+                {
+                    name.push(c);
+                }
+                self.channel(&name)
+            }
+            Some(_) => Err(self.error("expected a number, channel name or '('")),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    fn channel(&self, name: &str) -> Result<Expression, ComtradeError> {
+        self.record
+            .analog_channels
+            .iter()
+            .position(|c| field_matches(&c.config.name, name))
+            .map(Expression::Channel)
+            .ok_or_else(|| ComtradeError::AnalogChannelNameNotFound(name.trim().to_string()))
+    }
+}
+
+impl Expression {
+    /// Values of the expression at every sample, converting channels to primary
+    /// values first if `to_primary` is set.
+    fn evaluate(&self, record: &Comtrade, to_primary: bool) -> Vec<f64> {
+        let len = record.timestamps.len();
+        match self {
+            Expression::Number(value) => vec![*value; len],
+            Expression::Channel(channel) => {
+                let channel = &record.analog_channels[*channel];
+                if to_primary {
+                    channel.scaled_data(&AnalogScalingMode::Primary)
+                } else {
+                    channel.data.clone()
+                }
+            }
+            Expression::Negate(inner) => inner
+                .evaluate(record, to_primary)
+                .iter()
+                .map(|v| -v)
+                .collect(),
+            Expression::Binary(operator, left, right) => {
+                let (left, right) = (
+                    left.evaluate(record, to_primary),
+                    right.evaluate(record, to_primary),
+                );
+                left.iter()
+                    .zip(right.iter())
+                    .map(|(l, r)| match operator {
+                        Operator::Add => l + r,
+                        Operator::Subtract => l - r,
+                        Operator::Multiply => l * r,
+                        Operator::Divide => l / r,
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Units of the result, or `None` for a plain number.
+    fn units(&self, record: &Comtrade) -> Result<Option<String>, ComtradeError> {
+        match self {
+            Expression::Number(_) => Ok(None),
+            Expression::Channel(channel) => Ok(Some(
+                record.analog_channels[*channel]
+                    .config
+                    .units
+                    .trim()
+                    .to_string(),
+            )),
+            Expression::Negate(inner) => inner.units(record),
+            Expression::Binary(operator, left, right) => {
+                let (left, right) = (left.units(record)?, right.units(record)?);
+                match (operator, left, right) {
+                    (_, None, None) => Ok(None),
+                    (Operator::Add | Operator::Subtract, Some(l), Some(r)) if l != r => {
+                        Err(ComtradeError::MismatchedUnits(l, r))
+                    }
+                    (Operator::Multiply, Some(l), Some(r)) => Ok(Some(format!("{}*{}", l, r))),
+                    (Operator::Divide, Some(l), Some(r)) => Ok(Some(format!("{}/{}", l, r))),
+                    (Operator::Divide, None, Some(r)) => Ok(Some(format!("1/{}", r))),
+                    (_, Some(units), _) | (_, None, Some(units)) => Ok(Some(units)),
+                }
+            }
+        }
+    }
+
+    fn channels(&self, channels: &mut Vec<usize>) {
+        match self {
+            Expression::Number(_) => {}
+            Expression::Channel(channel) => channels.push(*channel),
+            Expression::Negate(inner) => inner.channels(channels),
+            Expression::Binary(_, left, right) => {
+                left.channels(channels);
+                right.channels(channels);
+            }
+        }
+    }
+}
+
+/// Multiplier which lets the largest value be stored in the data format.
+fn fit_multiplier(data: &[f64], data_format: &DataFormat) -> f64 {
+    let largest_raw = match data_format {
+        DataFormat::Ascii => 99999.0,
+        DataFormat::Binary16 => i16::MAX as f64,
+        DataFormat::Binary32 => i32::MAX as f64,
+        DataFormat::Float32 => return 1.0,
+    };
+    let largest = data
+        .iter()
+        .filter(|v| v.is_finite())
+        .fold(0.0, |m: f64, v| m.max(v.abs()));
+    if largest > 0.0 {
+        largest / largest_raw
+    } else {
+        1.0
+    }
+}
+
+/// Value of a config field if every channel has the same one, otherwise empty.
+fn common_field(configs: &[&AnalogConfig], field: impl Fn(&AnalogConfig) -> &str) -> String {
+    match configs.split_first() {
+        Some((first, rest)) if rest.iter().all(|c| field_matches(field(c), field(first))) => {
+            field(first).trim().to_string()
+        }
+        _ => String::new(),
+    }
+}
+
+impl Comtrade {
+    /// Calculate a new analog channel from an expression of existing ones, e.g.
+    /// `IA + IB + IC` or `(VA - VB) / 1000`. Expressions can use numbers, `+`,
+    /// `-`, `*`, `/` and brackets. Channels are referred to by name, compared like
+    /// [`Comtrade::analog_channel_by_name`]; names which aren't a single word go in
+    /// square brackets, e.g. `[Line 1 IA] - [Line 2 IA]`.
+    ///
+    /// The units of the new channel are worked out from the expression, and added
+    /// or subtracted channels must have the same units. The phase, circuit
+    /// component and primary/secondary scaling are kept where all the channels
+    /// used share them. Otherwise every channel is converted to primary values
+    /// before the expression is evaluated, and the new channel is primary. The
+    /// channel is indexed to go on the end of the record's
+    /// analog channels, with a multiplier chosen so its values fit the record's
+    /// data format.
+    pub fn derived_channel(
+        &self,
+        name: &str,
+        expression: &str,
+    ) -> Result<AnalogChannel, ComtradeError> {
+        let parsed = Parser {
+            source: expression,
+            chars: expression.char_indices().peekable(),
+            record: self,
+        }
+        .parse()?;
+
+        let units = parsed.units(self)?.unwrap_or_default();
+
+        let mut channels = Vec::new();
+        parsed.channels(&mut channels);
+        let configs: Vec<&AnalogConfig> = channels
+            .iter()
+            .map(|c| &self.analog_channels[*c].config)
+            .collect();
+        let same_scaling = configs.split_first().is_some_and(|(first, rest)| {
+            rest.iter().all(|c| {
+                c.primary_factor == first.primary_factor
+                    && c.secondary_factor == first.secondary_factor
+                    && c.scaling_mode == first.scaling_mode
+            })
+        });
+        let (primary_factor, secondary_factor, scaling_mode) = if same_scaling {
+            (
+                configs[0].primary_factor,
+                configs[0].secondary_factor,
+                configs[0].scaling_mode,
+            )
+        } else {
+            (1.0, 1.0, AnalogScalingMode::Primary)
+        };
+        let data = parsed.evaluate(self, !same_scaling);
+
+        let mut channel = AnalogChannel {
+            config: AnalogConfig {
+                index: (self.analog_channels.len() + 1).try_into().unwrap(),
+                name: name.to_string(),
+                phase: common_field(&configs, |c| &c.phase),
+                circuit_component_being_monitored: common_field(&configs, |c| {
+                    &c.circuit_component_being_monitored
+                }),
+                units,
+                min_value: 0.0,
+                max_value: 0.0,
+                multiplier: fit_multiplier(&data, &self.data_format),
+                offset_adder: 0.0,
+                skew: 0.0,
+                primary_factor,
+                secondary_factor,
+                scaling_mode,
+            },
+            data,
+        };
+        channel.recalculate_limits(&self.data_format);
+        Ok(channel)
+    }
+
+    /// Calculate a new analog channel with [`Comtrade::derived_channel`] and add
+    /// it to the end of the record, returning its 0-indexed position.
+    pub fn add_derived_channel(
+        &mut self,
+        name: &str,
+        expression: &str,
+    ) -> Result<usize, ComtradeError> {
+        let channel = self.derived_channel(name, expression)?;
+        self.analog_channels.push(channel);
+        Ok(self.analog_channels.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_multiplier_for_data_formats() {
+        let data = [-65534.0, 100.0];
+        assert_eq!(fit_multiplier(&data, &DataFormat::Binary16), 2.0);
+        assert_eq!(fit_multiplier(&data, &DataFormat::Float32), 1.0);
+        assert_eq!(fit_multiplier(&[0.0], &DataFormat::Binary16), 1.0);
+    }
+}
@@ -22,6 +22,59 @@ pub enum ComtradeError {
     ParserError(ParseError),
     #[error("Unable to find timestamp precision")]
     CantFindTimestampPrecision,
+    #[error("Sample range {start}..{end} is out of bounds for record with {len} samples.")]
+    SampleRangeOutOfBounds {
+        start: usize,
+        end: usize,
+        len: usize,
+    },
+    #[error("Range does not contain any samples.")]
+    EmptyRange,
+    #[error("No analog channel at position {0}.")]
+    AnalogChannelNotFound(usize),
+    #[error("No status channel at position {0}.")]
+    StatusChannelNotFound(usize),
+    #[error("At least one record is needed.")]
+    NoRecords,
+    #[error("Records do not overlap in time.")]
+    NoOverlap,
+    #[error("Records have different line frequencies: {0} Hz and {1} Hz.")]
+    MismatchedLineFrequency(f64, f64),
+    #[error("Invalid sampling rate: {0} Hz.")]
+    InvalidSamplingRate(f64),
+    #[error("Sampling rate of {0} Hz is below the input rate of {1} Hz and would alias.")]
+    SamplingRateTooLow(f64, f64),
+    #[error("Records have different numbers of channels.")]
+    MismatchedChannelCount,
+    #[error("Analog channel at position {0} has a different config between records.")]
+    MismatchedAnalogChannel(usize),
+    #[error("Status channel at position {0} has a different config between records.")]
+    MismatchedStatusChannel(usize),
+    #[error("Records are not contiguous; there is a gap of {0} s between them.")]
+    NotContiguous(f64),
+    #[error("Invalid line frequency: {0} Hz.")]
+    InvalidLineFrequency(f64),
+    #[error("Unrecognised units: '{0}'.")]
+    UnrecognisedUnits(String),
+    #[error("Invalid filter parameter {name}: {value}.")]
+    InvalidFilterParameter { name: &'static str, value: f64 },
+    #[error("Record must have a single sampling rate. Resample it first.")]
+    NonUniformSampling,
+    #[error(
+        "Invalid filter frequency: {0} Hz. Must be between 0 Hz and the Nyquist frequency, and \
+         the low edge of a band below the high edge."
+    )]
+    InvalidFilterFrequency(f64),
+    #[error("Invalid filter order or number of taps: {0}.")]
+    InvalidFilterOrder(usize),
+    #[error("Invalid expression at character {position}: {message}")]
+    InvalidExpression { position: usize, message: String },
+    #[error("No analog channel named '{0}'.")]
+    AnalogChannelNameNotFound(String),
+    #[error("Cannot add or subtract values in '{0}' and '{1}'.")]
+    MismatchedUnits(String, String),
+    #[error("Invalid window length: {0} s.")]
+    InvalidWindow(f64),
 }
 
 impl ComtradeError {
@@ -0,0 +1,455 @@
+use std::f64::consts::PI;
+
+use crate::error::ComtradeError;
+use crate::resample::{blackman, sinc};
+use crate::{AnalogChannel, Comtrade};
+
+/// Digital filter to apply to analog channel data. Frequencies are in Hz.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Butterworth IIR low-pass filter.
+    LowPass { cutoff_hz: f64, order: usize },
+
+    /// Butterworth IIR high-pass filter.
+    HighPass { cutoff_hz: f64, order: usize },
+
+    /// Butterworth IIR high-pass and low-pass filters in series.
+    BandPass {
+        low_hz: f64,
+        high_hz: f64,
+        order: usize,
+    },
+
+    /// Windowed-sinc FIR low-pass filter. Linear phase, delaying the signal by
+    /// half the number of taps unless applied with zero phase.
+    FirLowPass { cutoff_hz: f64, taps: usize },
+
+    /// Windowed-sinc FIR high-pass filter.
+    FirHighPass { cutoff_hz: f64, taps: usize },
+
+    /// Windowed-sinc FIR band-pass filter.
+    FirBandPass {
+        low_hz: f64,
+        high_hz: f64,
+        taps: usize,
+    },
+
+    /// Notches at the record's line frequency and its harmonics up to
+    /// `max_order`, each with the given quality factor (centre frequency over
+    /// bandwidth).
+    LineNotch { max_order: usize, quality: f64 },
+
+    /// Mimic filter which removes decaying DC offset with the given time constant
+    /// in seconds, as used by relays ahead of phasor estimation. The gain is
+    /// normalised to one at the line frequency.
+    Mimic { time_constant: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterOptions {
+    pub filter: Filter,
+
+    /// Run the filter forwards then backwards over the data, cancelling out its
+    /// phase shift (and squaring its magnitude response). Only possible offline,
+    /// but keeps filtered waveforms lined up with the originals.
+    pub zero_phase: bool,
+}
+
+/// Stage of a filter, as transfer function coefficients normalised so `a[0]` is 1.
+#[derive(Debug, Clone, PartialEq)]
+enum Stage {
+    Biquad { b: [f64; 3], a: [f64; 3] },
+    Fir(Vec<f64>),
+}
+
+impl Stage {
+    fn biquad(b: [f64; 3], a: [f64; 3]) -> Self {
+        Stage::Biquad {
+            b: [b[0] / a[0], b[1] / a[0], b[2] / a[0]],
+            a: [1.0, a[1] / a[0], a[2] / a[0]],
+        }
+    }
+
+    fn apply(&self, data: &[f64]) -> Vec<f64> {
+        match self {
+            Stage::Biquad { b, a } => {
+                // Transposed direct form II.
+                let (mut z1, mut z2) = (0.0, 0.0);
+                data.iter()
+                    .map(|x| {
+                        let y = b[0] * x + z1;
+                        z1 = b[1] * x - a[1] * y + z2;
+                        z2 = b[2] * x - a[2] * y;
+                        y
+                    })
+                    .collect()
+            }
+            Stage::Fir(taps) => (0..data.len())
+                .map(|n| {
+                    taps.iter()
+                        .take(n + 1)
+                        .enumerate()
+                        .map(|(k, tap)| tap * data[n - k])
+                        .sum()
+                })
+                .collect(),
+        }
+    }
+
+    /// Complex gain at `frequency`, as `(re, im)`.
+    #[cfg(test)]
+    fn response(&self, frequency: f64, rate_hz: f64) -> (f64, f64) {
+        let w = 2.0 * PI * frequency / rate_hz;
+        let polynomial = |coefficients: &[f64]| {
+            coefficients
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(re, im), (k, c)| {
+                    (re + c * (w * k as f64).cos(), im - c * (w * k as f64).sin())
+                })
+        };
+        match self {
+            Stage::Biquad { b, a } => {
+                let (nr, ni) = polynomial(b);
+                let (dr, di) = polynomial(a);
+                let d = dr * dr + di * di;
+                ((nr * dr + ni * di) / d, (ni * dr - nr * di) / d)
+            }
+            Stage::Fir(taps) => polynomial(taps),
+        }
+    }
+}
+
+fn check_frequency(frequency: f64, rate_hz: f64) -> Result<f64, ComtradeError> {
+    if frequency.is_finite() && frequency > 0.0 && frequency < rate_hz / 2.0 {
+        Ok(frequency)
+    } else {
+        Err(ComtradeError::InvalidFilterFrequency(frequency))
+    }
+}
+
+/// Band edges which are both valid frequencies, with the low edge below the
+/// high one.
+fn check_band(low_hz: f64, high_hz: f64, rate_hz: f64) -> Result<(f64, f64), ComtradeError> {
+    let (low_hz, high_hz) = (
+        check_frequency(low_hz, rate_hz)?,
+        check_frequency(high_hz, rate_hz)?,
+    );
+    if low_hz < high_hz {
+        Ok((low_hz, high_hz))
+    } else {
+        Err(ComtradeError::InvalidFilterFrequency(low_hz))
+    }
+}
+
+fn check_positive(name: &'static str, value: f64) -> Result<f64, ComtradeError> {
+    if value.is_finite() && value > 0.0 {
+        Ok(value)
+    } else {
+        Err(ComtradeError::InvalidFilterParameter { name, value })
+    }
+}
+
+fn check_order(order: usize) -> Result<usize, ComtradeError> {
+    if order == 0 {
+        Err(ComtradeError::InvalidFilterOrder(order))
+    } else {
+        Ok(order)
+    }
+}
+
+/// Butterworth filter as second order sections (plus a first order section for
+/// odd orders), by the bilinear transform.
+fn butterworth(cutoff_hz: f64, order: usize, rate_hz: f64, high_pass: bool) -> Vec<Stage> {
+    let w0 = 2.0 * PI * cutoff_hz / rate_hz;
+    let (sin, cos) = w0.sin_cos();
+    let mut stages = Vec::new();
+
+    for k in 0..order / 2 {
+        let q = 1.0 / (2.0 * ((2 * k + 1) as f64 * PI / (2 * order) as f64).sin());
+        let alpha = sin / (2.0 * q);
+        let a = [1.0 + alpha, -2.0 * cos, 1.0 - alpha];
+        let b = if high_pass {
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0]
+        } else {
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0]
+        };
+        stages.push(Stage::biquad(b, a));
+    }
+
+    if order % 2 == 1 {
+        let k = (w0 / 2.0).tan();
+        let a = [1.0 + k, k - 1.0, 0.0];
+        let b = if high_pass {
+            [1.0, -1.0, 0.0]
+        } else {
+            [k, k, 0.0]
+        };
+        stages.push(Stage::biquad(b, a));
+    }
+    stages
+}
+
+/// Blackman windowed-sinc low-pass kernel with unity gain at DC.
+fn fir_low_pass(cutoff_hz: f64, taps: usize, rate_hz: f64) -> Vec<f64> {
+    let fc = cutoff_hz / rate_hz;
+    let middle = (taps - 1) as f64 / 2.0;
+    let mut kernel: Vec<f64> = (0..taps)
+        .map(|n| {
+            let offset = n as f64 - middle;
+            let window = if taps > 1 {
+                blackman(offset / (middle + 1.0))
+            } else {
+                1.0
+            };
+            2.0 * fc * sinc(2.0 * fc * offset) * window
+        })
+        .collect();
+    let total: f64 = kernel.iter().sum();
+    kernel.iter_mut().for_each(|k| *k /= total);
+    kernel
+}
+
+/// High-pass kernel by spectral inversion of a low-pass one. Needs an odd number
+/// of taps so there's a middle tap.
+fn fir_high_pass(cutoff_hz: f64, taps: usize, rate_hz: f64) -> Vec<f64> {
+    let mut kernel: Vec<f64> = fir_low_pass(cutoff_hz, taps, rate_hz)
+        .iter()
+        .map(|k| -k)
+        .collect();
+    kernel[taps / 2] += 1.0;
+    kernel
+}
+
+fn check_fir_taps(taps: usize) -> Result<usize, ComtradeError> {
+    if taps % 2 == 1 {
+        Ok(taps)
+    } else {
+        Err(ComtradeError::InvalidFilterOrder(taps))
+    }
+}
+
+impl Filter {
+    fn stages(&self, rate_hz: f64, line_frequency: f64) -> Result<Vec<Stage>, ComtradeError> {
+        let stages = match *self {
+            Filter::LowPass { cutoff_hz, order } => butterworth(
+                check_frequency(cutoff_hz, rate_hz)?,
+                check_order(order)?,
+                rate_hz,
+                false,
+            ),
+            Filter::HighPass { cutoff_hz, order } => butterworth(
+                check_frequency(cutoff_hz, rate_hz)?,
+                check_order(order)?,
+                rate_hz,
+                true,
+            ),
+            Filter::BandPass {
+                low_hz,
+                high_hz,
+                order,
+            } => {
+                let (low_hz, high_hz) = check_band(low_hz, high_hz, rate_hz)?;
+                let order = check_order(order)?;
+                let mut stages = butterworth(low_hz, order, rate_hz, true);
+                stages.extend(butterworth(high_hz, order, rate_hz, false));
+                stages
+            }
+            Filter::FirLowPass { cutoff_hz, taps } => vec![Stage::Fir(fir_low_pass(
+                check_frequency(cutoff_hz, rate_hz)?,
+                check_fir_taps(taps)?,
+                rate_hz,
+            ))],
+            Filter::FirHighPass { cutoff_hz, taps } => vec![Stage::Fir(fir_high_pass(
+                check_frequency(cutoff_hz, rate_hz)?,
+                check_fir_taps(taps)?,
+                rate_hz,
+            ))],
+            Filter::FirBandPass {
+                low_hz,
+                high_hz,
+                taps,
+            } => {
+                let (low_hz, high_hz) = check_band(low_hz, high_hz, rate_hz)?;
+                let taps = check_fir_taps(taps)?;
+                let high = fir_low_pass(high_hz, taps, rate_hz);
+                let low = fir_low_pass(low_hz, taps, rate_hz);
+                vec![Stage::Fir(
+                    high.iter().zip(low.iter()).map(|(h, l)| h - l).collect(),
+                )]
+            }
+            Filter::LineNotch { max_order, quality } => {
+                let quality = check_positive("quality", quality)?;
+                (1..=max_order)
+                    .map(|order| order as f64 * line_frequency)
+                    .take_while(|frequency| *frequency < rate_hz / 2.0)
+                    .map(|frequency| {
+                        let w0 = 2.0 * PI * frequency / rate_hz;
+                        let alpha = w0.sin() / (2.0 * quality);
+                        Stage::biquad(
+                            [1.0, -2.0 * w0.cos(), 1.0],
+                            [1.0 + alpha, -2.0 * w0.cos(), 1.0 - alpha],
+                        )
+                    })
+                    .collect()
+            }
+            Filter::Mimic { time_constant } => {
+                let ratio = check_positive("time constant", time_constant)? * rate_hz;
+                let w = 2.0 * PI * line_frequency / rate_hz;
+                let (re, im) = (1.0 + ratio - ratio * w.cos(), ratio * w.sin());
+                let gain = 1.0 / (re * re + im * im).sqrt();
+                vec![Stage::Fir(vec![gain * (1.0 + ratio), -gain * ratio])]
+            }
+        };
+        Ok(stages)
+    }
+}
+
+fn apply_stages(stages: &[Stage], data: &[f64]) -> Vec<f64> {
+    stages
+        .iter()
+        .fold(data.to_vec(), |data, stage| stage.apply(&data))
+}
+
+impl Comtrade {
+    /// The single sampling rate of the record, which filters are designed for.
+    fn uniform_sampling_rate(&self) -> Result<f64, ComtradeError> {
+        match self.sampling_rates.as_slice() {
+            [first, rest @ ..] if rest.iter().all(|r| r.rate_hz == first.rate_hz) => {
+                Ok(first.rate_hz)
+            }
+            _ => Err(ComtradeError::NonUniformSampling),
+        }
+    }
+
+    fn filtered_data(
+        &self,
+        channel: usize,
+        options: &FilterOptions,
+    ) -> Result<Vec<f64>, ComtradeError> {
+        let data = &self
+            .analog_channels
+            .get(channel)
+            .ok_or(ComtradeError::AnalogChannelNotFound(channel))?
+            .data;
+        if matches!(
+            options.filter,
+            Filter::LineNotch { .. } | Filter::Mimic { .. }
+        ) {
+            self.check_line_frequency()?;
+        }
+        let stages = options
+            .filter
+            .stages(self.uniform_sampling_rate()?, self.line_frequency)?;
+
+        let mut filtered = apply_stages(&stages, data);
+        if options.zero_phase {
+            filtered.reverse();
+            filtered = apply_stages(&stages, &filtered);
+            filtered.reverse();
+        }
+        Ok(filtered)
+    }
+
+    /// Filter an analog channel's data in place. The record must have a single
+    /// sampling rate; use [`Comtrade::resample`] first if it doesn't.
+    ///
+    /// Filters start from rest, so expect a start-up transient at the beginning of
+    /// the data (and at the end too when filtering with zero phase).
+    pub fn filter_channel(
+        &mut self,
+        channel: usize,
+        options: &FilterOptions,
+    ) -> Result<(), ComtradeError> {
+        let filtered = self.filtered_data(channel, options)?;
+        let analog = &mut self.analog_channels[channel];
+        analog.data = filtered;
+        analog.recalculate_limits(&self.data_format);
+        Ok(())
+    }
+
+    /// Filtered copy of an analog channel with a new name, indexed to go on the
+    /// end of the record's analog channels. See [`Comtrade::filter_channel`].
+    pub fn filtered_channel(
+        &self,
+        channel: usize,
+        options: &FilterOptions,
+        name: &str,
+    ) -> Result<AnalogChannel, ComtradeError> {
+        let data = self.filtered_data(channel, options)?;
+        let mut config = self.analog_channels[channel].config.clone();
+        config.name = name.to_string();
+        config.index = (self.analog_channels.len() + 1).try_into().unwrap();
+
+        let mut filtered = AnalogChannel { config, data };
+        filtered.recalculate_limits(&self.data_format);
+        Ok(filtered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gain(stages: &[Stage], frequency: f64, rate_hz: f64) -> f64 {
+        stages
+            .iter()
+            .map(|s| {
+                let (re, im) = s.response(frequency, rate_hz);
+                (re * re + im * im).sqrt()
+            })
+            .product()
+    }
+
+    #[test]
+    fn butterworth_low_pass_response() {
+        for order in [1, 2, 5] {
+            let stages = butterworth(100.0, order, 1000.0, false);
+            assert!((gain(&stages, 0.0, 1000.0) - 1.0).abs() < 1e-9);
+            assert!((gain(&stages, 100.0, 1000.0) - 0.5f64.sqrt()).abs() < 1e-9);
+            assert!(gain(&stages, 300.0, 1000.0) < 0.5f64.powi(order as i32));
+        }
+    }
+
+    #[test]
+    fn butterworth_high_pass_response() {
+        let stages = butterworth(100.0, 3, 1000.0, true);
+        assert!(gain(&stages, 0.0, 1000.0) < 1e-9);
+        assert!((gain(&stages, 100.0, 1000.0) - 0.5f64.sqrt()).abs() < 1e-9);
+        assert!((gain(&stages, 499.0, 1000.0) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn fir_high_pass_blocks_dc() {
+        let kernel = fir_high_pass(100.0, 51, 1000.0);
+        assert!(kernel.iter().sum::<f64>().abs() < 1e-12);
+    }
+
+    #[test]
+    fn notches_remove_line_frequency_and_harmonics() {
+        let filter = Filter::LineNotch {
+            max_order: 3,
+            quality: 5.0,
+        };
+        let stages = filter.stages(1200.0, 60.0).unwrap();
+        assert_eq!(stages.len(), 3);
+        for frequency in [60.0, 120.0, 180.0] {
+            assert!(gain(&stages, frequency, 1200.0) < 1e-9);
+        }
+        assert!(gain(&stages, 0.0, 1200.0) > 0.99);
+    }
+
+    #[test]
+    fn mimic_has_unity_gain_at_line_frequency() {
+        let filter = Filter::Mimic {
+            time_constant: 0.05,
+        };
+        let stages = filter.stages(1200.0, 60.0).unwrap();
+        assert!((gain(&stages, 60.0, 1200.0) - 1.0).abs() < 1e-12);
+
+        // Cancels out an exponential decaying with the time constant.
+        let decay: Vec<f64> = (0..100).map(|n| (-n as f64 / 60.0).exp()).collect();
+        let filtered = apply_stages(&stages, &decay);
+        assert!(filtered[1..].iter().all(|v| v.abs() < 1e-3));
+    }
+}
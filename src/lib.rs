@@ -1,13 +1,30 @@
+pub mod analysis;
+mod concat;
+mod derived;
 mod error;
+mod filter;
+mod merge;
 pub mod parser;
+mod query;
+mod resample;
+mod select;
+mod slice;
+pub mod status;
+mod window;
 
 use chrono::{FixedOffset, NaiveDateTime};
 use derive_builder::Builder;
 
+pub use error::ComtradeError;
+pub use filter::{Filter, FilterOptions};
+pub use merge::MergedRecord;
 pub use parser::{
     AnalogChannel, AnalogConfig, AnalogScalingMode, ComtradeParser, ComtradeParserBuilder,
     DataFormat, FormatRevision, ParseError, ParseResult, SamplingRate, StatusChannel, StatusConfig,
 };
+pub use resample::ResampleOptions;
+pub use slice::SliceRange;
+pub use window::{Interpolation, RecordWindow};
 
 #[derive(Debug, Clone, PartialEq)]
 enum FileType {
@@ -45,6 +62,19 @@ pub enum TimeQuality {
     ClockFailure,
 }
 
+impl TimeQuality {
+    /// Uncertainty of the clock time in seconds, or `None` if the clock has failed
+    /// and the time can't be relied upon at all. A locked clock is taken to have no
+    /// uncertainty beyond the timestamp precision.
+    pub fn uncertainty(&self) -> Option<f64> {
+        match self {
+            TimeQuality::ClockLocked => Some(0.0),
+            TimeQuality::ClockUnlocked(power) => Some(10f64.powi(*power)),
+            TimeQuality::ClockFailure => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum LeapSecondStatus {
     /// Time source does not have capability to address presence of leap seconds.
@@ -116,3 +146,28 @@ impl Default for Comtrade {
         }
     }
 }
+
+impl Comtrade {
+    /// Copy of the record with all the header information but no channels or samples.
+    pub(crate) fn clone_without_data(&self) -> Comtrade {
+        Comtrade {
+            station_name: self.station_name.clone(),
+            recording_device_id: self.recording_device_id.clone(),
+            revision: self.revision,
+            sample_numbers: vec![],
+            timestamps: vec![],
+            analog_channels: vec![],
+            status_channels: vec![],
+            line_frequency: self.line_frequency,
+            sampling_rates: self.sampling_rates.clone(),
+            start_time: self.start_time,
+            trigger_time: self.trigger_time,
+            data_format: self.data_format.clone(),
+            timestamp_multiplication_factor: self.timestamp_multiplication_factor,
+            time_offset: self.time_offset,
+            local_offset: self.local_offset,
+            time_quality: self.time_quality.clone(),
+            leap_second_status: self.leap_second_status.clone(),
+        }
+    }
+}
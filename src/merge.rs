@@ -0,0 +1,204 @@
+use chrono::{Duration, NaiveDateTime};
+
+use crate::error::ComtradeError;
+use crate::select::reindex;
+use crate::window::{duration_to_seconds, seconds_to_duration};
+use crate::{AnalogChannel, Comtrade, Interpolation, SamplingRate, StatusChannel, TimeQuality};
+
+/// Several records combined onto a common timeline by [`Comtrade::merge`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedRecord {
+    pub record: Comtrade,
+
+    /// Worst-case error in seconds in the alignment of any two of the source
+    /// records, based on the time quality of their clocks. `None` if any of the
+    /// records has a failed clock or doesn't specify its time quality (i.e. is
+    /// pre-2013 format), in which case the alignment can't be trusted.
+    pub alignment_uncertainty: Option<f64>,
+}
+
+/// Start time of the record in UTC. Records without a time offset are assumed
+/// to already be in UTC.
+pub(crate) fn utc_start_time(record: &Comtrade) -> NaiveDateTime {
+    match record.time_offset {
+        Some(offset) => record.start_time - Duration::seconds(offset.local_minus_utc() as i64),
+        None => record.start_time,
+    }
+}
+
+/// Highest sampling rate of a record in Hz, from its shortest sample interval so
+/// that records with critical timestamps are covered too.
+fn highest_rate(record: &Comtrade) -> f64 {
+    let shortest = record
+        .timestamps
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|interval| *interval > 0.0)
+        .fold(f64::INFINITY, f64::min);
+    1.0 / shortest
+}
+
+fn utc_trigger_time(record: &Comtrade) -> NaiveDateTime {
+    utc_start_time(record) + (record.trigger_time - record.start_time)
+}
+
+fn alignment_uncertainty(records: &[Comtrade]) -> Option<f64> {
+    let mut uncertainties = records
+        .iter()
+        .map(|r| r.time_quality.as_ref().and_then(|q| q.uncertainty()))
+        .collect::<Option<Vec<f64>>>()?;
+
+    // The two records with the worst clocks could be out in opposite directions.
+    uncertainties.sort_by(|a, b| b.total_cmp(a));
+    Some(uncertainties.iter().take(2).sum())
+}
+
+pub(crate) fn worst_time_quality<'a>(
+    qualities: impl Iterator<Item = &'a Option<TimeQuality>>,
+) -> Option<TimeQuality> {
+    qualities
+        .cloned()
+        .collect::<Option<Vec<TimeQuality>>>()?
+        .into_iter()
+        .max_by(|a, b| {
+            let a = a.uncertainty().unwrap_or(f64::INFINITY);
+            let b = b.uncertainty().unwrap_or(f64::INFINITY);
+            a.total_cmp(&b)
+        })
+}
+
+impl Comtrade {
+    /// Combine records from several devices onto a common timeline.
+    ///
+    /// Records are aligned on their absolute start times, using the time offset
+    /// from UTC where present, and cropped to the period covered by all of them.
+    /// Every channel is then resampled at `rate_hz` by linear interpolation
+    /// (status channels hold their last value). Interpolation doesn't filter out
+    /// anything above the new Nyquist frequency, so `rate_hz` must be at least the
+    /// highest sampling rate of any of the records; downsample them first with
+    /// [`Comtrade::resample`] to go lower. Channel names are prefixed with the
+    /// station name and recording device ID of the record they came from, e.g.
+    /// `SMARTSTATION/IED123/IA`, and channels are reindexed in order.
+    ///
+    /// The merged record takes its header information from the first record and
+    /// its times are in the first record's local time. The trigger time is the
+    /// earliest of the records' triggers.
+    pub fn merge(records: &[Comtrade], rate_hz: f64) -> Result<MergedRecord, ComtradeError> {
+        let first = records.first().ok_or(ComtradeError::NoRecords)?;
+        if !rate_hz.is_finite() || rate_hz <= 0.0 {
+            return Err(ComtradeError::InvalidSamplingRate(rate_hz));
+        }
+        let input_rate = records.iter().map(highest_rate).fold(0.0, f64::max);
+        if rate_hz < input_rate * (1.0 - 1e-6) {
+            return Err(ComtradeError::SamplingRateTooLow(rate_hz, input_rate));
+        }
+        if let Some(other) = records
+            .iter()
+            .find(|r| r.line_frequency != first.line_frequency)
+        {
+            return Err(ComtradeError::MismatchedLineFrequency(
+                first.line_frequency,
+                other.line_frequency,
+            ));
+        }
+
+        let mut common_start: Option<NaiveDateTime> = None;
+        let mut common_end: Option<NaiveDateTime> = None;
+        for record in records {
+            let (first_ts, last_ts) = match (record.timestamps.first(), record.timestamps.last()) {
+                (Some(first_ts), Some(last_ts)) => (*first_ts, *last_ts),
+                _ => return Err(ComtradeError::NoOverlap),
+            };
+            let start = utc_start_time(record) + seconds_to_duration(first_ts);
+            let end = utc_start_time(record) + seconds_to_duration(last_ts);
+            common_start = Some(common_start.map_or(start, |s| s.max(start)));
+            common_end = Some(common_end.map_or(end, |e| e.min(end)));
+        }
+
+        // Both set, as there is at least one record.
+        let (common_start, common_end) = (common_start.unwrap(), common_end.unwrap());
+        if common_end < common_start {
+            return Err(ComtradeError::NoOverlap);
+        }
+
+        let num_samples =
+            (duration_to_seconds(common_end - common_start) * rate_hz + 1e-6).floor() as usize + 1;
+        let timestamps: Vec<f64> = (0..num_samples).map(|i| i as f64 / rate_hz).collect();
+
+        let mut analog_channels: Vec<AnalogChannel> = Vec::new();
+        let mut status_channels: Vec<StatusChannel> = Vec::new();
+
+        for record in records {
+            let prefix = format!(
+                "{}/{}/",
+                record.station_name.trim(),
+                record.recording_device_id.trim()
+            );
+
+            // Time of the common start relative to the start of this record.
+            let offset = duration_to_seconds(common_start - utc_start_time(record));
+            let (first_ts, last_ts) = (
+                record.timestamps[0],
+                record.timestamps[record.timestamps.len() - 1],
+            );
+            let local_times: Vec<f64> = timestamps
+                .iter()
+                .map(|t| (t + offset).clamp(first_ts, last_ts))
+                .collect();
+
+            for (channel_idx, channel) in record.analog_channels.iter().enumerate() {
+                let mut config = channel.config.clone();
+                config.name = format!("{}{}", prefix, config.name.trim());
+                let data = local_times
+                    .iter()
+                    .map(|t| {
+                        record
+                            .value_at_time(channel_idx, *t, Interpolation::Linear)
+                            .unwrap_or(0.0)
+                    })
+                    .collect();
+                analog_channels.push(AnalogChannel { config, data });
+            }
+
+            for (channel_idx, channel) in record.status_channels.iter().enumerate() {
+                let mut config = channel.config.clone();
+                config.name = format!("{}{}", prefix, config.name.trim());
+                let data = local_times
+                    .iter()
+                    .map(|t| record.status_at_time(channel_idx, *t).unwrap_or(0))
+                    .collect();
+                status_channels.push(StatusChannel { config, data });
+            }
+        }
+
+        reindex(analog_channels.iter_mut().map(|c| &mut c.config.index));
+        reindex(status_channels.iter_mut().map(|c| &mut c.config.index));
+        for channel in analog_channels.iter_mut() {
+            channel.recalculate_limits(&first.data_format);
+        }
+
+        let utc_minus_local = utc_start_time(first) - first.start_time;
+        let trigger_time = records.iter().map(utc_trigger_time).min().unwrap();
+
+        let record = Comtrade {
+            sample_numbers: (1..=num_samples as u32).collect(),
+            timestamps,
+            analog_channels,
+            status_channels,
+            sampling_rates: vec![SamplingRate {
+                rate_hz,
+                end_sample_number: num_samples as u32,
+            }],
+            start_time: common_start - utc_minus_local,
+            trigger_time: trigger_time - utc_minus_local,
+            timestamp_multiplication_factor: 1.0,
+            time_quality: worst_time_quality(records.iter().map(|r| &r.time_quality)),
+            ..first.clone_without_data()
+        };
+
+        Ok(MergedRecord {
+            record,
+            alignment_uncertainty: alignment_uncertainty(records),
+        })
+    }
+}
@@ -2,7 +2,7 @@ use super::ConfigLine;
 use crate::error::ComtradeError;
 use std::num::NonZeroUsize;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AnalogScalingMode {
     Primary,
     Secondary,
@@ -30,6 +30,20 @@ pub struct AnalogConfig {
 }
 
 impl AnalogConfig {
+    /// Factor to multiply values by to convert them from the channel's scaling
+    /// mode into the given one, using the primary and secondary factors.
+    pub fn scaling_factor(&self, target: &AnalogScalingMode) -> f64 {
+        match (&self.scaling_mode, target) {
+            (AnalogScalingMode::Secondary, AnalogScalingMode::Primary) => {
+                self.primary_factor / self.secondary_factor
+            }
+            (AnalogScalingMode::Primary, AnalogScalingMode::Secondary) => {
+                self.secondary_factor / self.primary_factor
+            }
+            _ => 1.0,
+        }
+    }
+
     pub fn from_cfg_row<'a>(mut config_line: impl ConfigLine<'a>) -> Result<Self, ComtradeError> {
         let index = config_line.read_value()?;
         let name = config_line.read_value()?;
@@ -46,8 +46,6 @@ impl<T: BufRead> ComtradeParser<T> {
         self.num_analog_channels = num_analog_channels;
         self.num_status_channels = num_status_channels;
 
-        line_number += 1;
-
         let mut analog_channels: Vec<AnalogConfig> =
             Vec::with_capacity(self.num_analog_channels as usize);
         let mut status_channels: Vec<StatusConfig> =
@@ -100,18 +98,18 @@ impl<T: BufRead> ComtradeParser<T> {
             sampling_rates.push(sampling_rate);
         }
 
-        self.total_num_samples = sampling_rates
-            .iter()
-            .map(|r| r.end_sample_number)
-            .max()
-            .unwrap() as usize;
-
         // If file has 0 for number of sample rates, there's an extra line which just contains 0
-        // indicating no fixed sample rate and the total number of samples. We don't need this data
-        // so we just ignore it.
-        if num_sampling_rates == 0 {
-            lines.next().ok_or_else(early_end_err)?;
-        }
+        // indicating no fixed sample rate and the total number of samples.
+        self.total_num_samples = if num_sampling_rates == 0 {
+            let line = lines.next().ok_or_else(early_end_err)?;
+            SamplingRate::from_config_line(split_cfg_line(line))?.end_sample_number as usize
+        } else {
+            sampling_rates
+                .iter()
+                .map(|r| r.end_sample_number)
+                .max()
+                .unwrap() as usize
+        };
 
         self.is_timestamp_critical = num_sampling_rates == 0;
         self.builder.sampling_rates(sampling_rates);
@@ -285,4 +283,24 @@ mod tests {
         assert_eq!(sizes.analog, 4);
         assert_eq!(sizes.status, 16);
     }
+
+    #[test]
+    fn total_samples_read_from_extra_line_when_no_sampling_rates() {
+        let cfg = "station,equipment,1999
+1,1A,0D
+1,VA,A,obj,kV,0.001,0.0,0.0,-32767,32767,120.0,1.0,P
+60
+0
+0, 8000
+17/02/2021,22:27:49.159106
+17/02/2021,22:27:50.657858
+BINARY
+1.0
+";
+        let mut parser = ComtradeParser::<&[u8]>::new(None, None, None, None, None);
+        parser.cfg_contents = cfg.to_string();
+        parser.parse_cfg().unwrap();
+        assert_eq!(parser.total_num_samples, 8000);
+        assert!(parser.is_timestamp_critical);
+    }
 }
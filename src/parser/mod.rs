@@ -137,7 +137,44 @@ impl AnalogChannel {
         self.data.push(value);
     }
 
-    // TODO: Method for retrieving datum at index / sample number including value and time calculations.
+    /// Channel data converted to primary or secondary values.
+    pub fn scaled_data(&self, mode: &AnalogScalingMode) -> Vec<f64> {
+        let factor = self.config.scaling_factor(mode);
+        self.data.iter().map(|v| v * factor).collect()
+    }
+
+    /// Convert a real value back into the value as it would be stored in the data
+    /// file, i.e. undo the multiplier and offset adder.
+    pub fn raw_value(&self, value: f64) -> f64 {
+        (value - self.config.offset_adder) / self.config.multiplier
+    }
+
+    /// Recalculate the config `min_value` and `max_value` from the current data.
+    /// These are in raw data file units, so are rounded to whole numbers for the
+    /// integer binary data formats.
+    pub fn recalculate_limits(&mut self, data_format: &DataFormat) {
+        if self.data.is_empty() || self.config.multiplier == 0.0 {
+            return;
+        }
+
+        let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+        for value in self.data.iter() {
+            let raw = self.raw_value(*value);
+            min = min.min(raw);
+            max = max.max(raw);
+        }
+
+        match data_format {
+            DataFormat::Binary16 | DataFormat::Binary32 => {
+                self.config.min_value = min.round();
+                self.config.max_value = max.round();
+            }
+            DataFormat::Ascii | DataFormat::Float32 => {
+                self.config.min_value = min;
+                self.config.max_value = max;
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -150,8 +187,6 @@ impl StatusChannel {
     fn push_datum(&mut self, value: u8) {
         self.data.push(value);
     }
-
-    // TODO: Method for retrieving datum at index / sample number including time calculations.
 }
 
 // Cannot derive builder for this because of complexity of wrapping `T: BufRead` in
@@ -0,0 +1,157 @@
+use regex::Regex;
+
+use crate::{AnalogChannel, Comtrade, StatusChannel};
+
+/// Remove surrounding whitespace and collapse internal runs of whitespace to a
+/// single space. Channel names in the wild are often padded out to a fixed width
+/// (e.g. `J1 -IA              `), which makes naive comparison unreliable.
+pub(crate) fn collapse_whitespace(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Normalise a channel field for comparison, ignoring whitespace padding and case.
+pub(crate) fn normalise_field(value: &str) -> String {
+    collapse_whitespace(value).to_lowercase()
+}
+
+pub(crate) fn field_matches(field: &str, query: &str) -> bool {
+    normalise_field(field) == normalise_field(query)
+}
+
+/// Convert a shell-style glob (`*` matches any run of characters, `?` matches a
+/// single character) into an anchored, case-insensitive regular expression.
+pub(crate) fn glob_to_regex(glob: &str) -> Regex {
+    let mut pattern = String::from("(?i)^");
+    for c in collapse_whitespace(glob).chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            _ => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+
+    // Everything other than the wildcards is escaped, so this can't fail.
+    Regex::new(&pattern).unwrap()
+}
+
+impl Comtrade {
+    /// Find the analog channel with the given name. Names are compared after
+    /// trimming, collapsing whitespace and ignoring case.
+    pub fn analog_channel_by_name(&self, name: &str) -> Option<&AnalogChannel> {
+        self.analog_channels
+            .iter()
+            .find(|c| field_matches(&c.config.name, name))
+    }
+
+    /// Find the status channel with the given name. Names are compared after
+    /// trimming, collapsing whitespace and ignoring case.
+    pub fn status_channel_by_name(&self, name: &str) -> Option<&StatusChannel> {
+        self.status_channels
+            .iter()
+            .find(|c| field_matches(&c.config.name, name))
+    }
+
+    /// Find all analog channels whose name matches the regular expression. The name
+    /// has its whitespace trimmed and collapsed before matching but keeps its case,
+    /// so use `(?i)` in the pattern for case-insensitive matching.
+    pub fn analog_channels_matching(&self, pattern: &Regex) -> Vec<&AnalogChannel> {
+        self.analog_channels
+            .iter()
+            .filter(|c| pattern.is_match(&collapse_whitespace(&c.config.name)))
+            .collect()
+    }
+
+    /// Find all status channels whose name matches the regular expression. See
+    /// [`Comtrade::analog_channels_matching`] for how names are normalised.
+    pub fn status_channels_matching(&self, pattern: &Regex) -> Vec<&StatusChannel> {
+        self.status_channels
+            .iter()
+            .filter(|c| pattern.is_match(&collapse_whitespace(&c.config.name)))
+            .collect()
+    }
+
+    /// Find all analog channels whose name matches a glob such as `J1 -I*`.
+    pub fn analog_channels_matching_glob(&self, glob: &str) -> Vec<&AnalogChannel> {
+        self.analog_channels_matching(&glob_to_regex(glob))
+    }
+
+    /// Find all status channels whose name matches a glob such as `Ph * OP`.
+    pub fn status_channels_matching_glob(&self, glob: &str) -> Vec<&StatusChannel> {
+        self.status_channels_matching(&glob_to_regex(glob))
+    }
+
+    /// Find all analog channels on the given phase of the given circuit component,
+    /// e.g. phase `A` of `Line123`. An empty component matches channels which don't
+    /// specify one.
+    pub fn analog_channels_by_phase(&self, phase: &str, component: &str) -> Vec<&AnalogChannel> {
+        self.analog_channels
+            .iter()
+            .filter(|c| {
+                field_matches(&c.config.phase, phase)
+                    && field_matches(&c.config.circuit_component_being_monitored, component)
+            })
+            .collect()
+    }
+
+    /// Find all status channels on the given phase of the given circuit component.
+    pub fn status_channels_by_phase(&self, phase: &str, component: &str) -> Vec<&StatusChannel> {
+        self.status_channels
+            .iter()
+            .filter(|c| {
+                field_matches(&c.config.phase, phase)
+                    && field_matches(&c.config.circuit_component_being_monitored, component)
+            })
+            .collect()
+    }
+
+    /// Find all analog channels monitoring the given circuit component.
+    pub fn analog_channels_by_component(&self, component: &str) -> Vec<&AnalogChannel> {
+        self.analog_channels
+            .iter()
+            .filter(|c| field_matches(&c.config.circuit_component_being_monitored, component))
+            .collect()
+    }
+
+    /// Find all status channels monitoring the given circuit component.
+    pub fn status_channels_by_component(&self, component: &str) -> Vec<&StatusChannel> {
+        self.status_channels
+            .iter()
+            .filter(|c| field_matches(&c.config.circuit_component_being_monitored, component))
+            .collect()
+    }
+
+    /// Find all analog channels measured in the given units, e.g. `kV` or `A`.
+    ///
+    /// Units are compared case-sensitively (after trimming) because case is
+    /// significant for SI prefixes - `mA` and `MA` are very different things.
+    pub fn analog_channels_by_units(&self, units: &str) -> Vec<&AnalogChannel> {
+        self.analog_channels
+            .iter()
+            .filter(|c| c.config.units.trim() == units.trim())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalise_field_trims_and_collapses_whitespace() {
+        assert_eq!(normalise_field("  J1   -IA    "), "j1 -ia");
+        assert_eq!(normalise_field(""), "");
+    }
+
+    #[test]
+    fn glob_matches_wildcards_and_escapes_everything_else() {
+        let re = glob_to_regex("J1 I?*");
+        assert!(re.is_match("J1 Ia Angle"));
+        assert!(re.is_match("j1 ib"));
+        assert!(!re.is_match("J1 I"));
+
+        let re = glob_to_regex("3I0 (calc.)");
+        assert!(re.is_match("3I0 (calc.)"));
+        assert!(!re.is_match("3I0 xcalcx"));
+    }
+}
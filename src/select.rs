@@ -0,0 +1,120 @@
+use std::num::NonZeroUsize;
+
+use crate::error::ComtradeError;
+use crate::{AnalogChannel, AnalogConfig, Comtrade, StatusChannel, StatusConfig};
+
+/// Renumber channel indices so they run from 1 in the order given.
+pub(crate) fn reindex<'a>(indices: impl Iterator<Item = &'a mut NonZeroUsize>) {
+    for (i, index) in indices.enumerate() {
+        *index = NonZeroUsize::new(i + 1).unwrap();
+    }
+}
+
+impl Comtrade {
+    /// New record containing only the given channels, in the given order. Channels
+    /// are specified by their 0-indexed position in `analog_channels` and
+    /// `status_channels`, and are reindexed from 1 in the new record.
+    ///
+    /// ```rust,no_run
+    /// # fn example(record: comtrade::Comtrade) -> Result<(), comtrade::ComtradeError> {
+    /// // Keep the first three analog channels, with the third first, and no status channels.
+    /// let currents = record.select_channels(&[2, 0, 1], &[])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn select_channels(
+        &self,
+        analog: &[usize],
+        status: &[usize],
+    ) -> Result<Comtrade, ComtradeError> {
+        let mut analog_channels = analog
+            .iter()
+            .map(|i| {
+                self.analog_channels
+                    .get(*i)
+                    .cloned()
+                    .ok_or(ComtradeError::AnalogChannelNotFound(*i))
+            })
+            .collect::<Result<Vec<AnalogChannel>, ComtradeError>>()?;
+        let mut status_channels = status
+            .iter()
+            .map(|i| {
+                self.status_channels
+                    .get(*i)
+                    .cloned()
+                    .ok_or(ComtradeError::StatusChannelNotFound(*i))
+            })
+            .collect::<Result<Vec<StatusChannel>, ComtradeError>>()?;
+
+        reindex(analog_channels.iter_mut().map(|c| &mut c.config.index));
+        reindex(status_channels.iter_mut().map(|c| &mut c.config.index));
+
+        Ok(Comtrade {
+            sample_numbers: self.sample_numbers.clone(),
+            timestamps: self.timestamps.clone(),
+            analog_channels,
+            status_channels,
+            ..self.clone_without_data()
+        })
+    }
+
+    /// New record without the given channels. Channels are specified by their
+    /// 0-indexed position, and the remaining channels are reindexed from 1.
+    pub fn remove_channels(
+        &self,
+        analog: &[usize],
+        status: &[usize],
+    ) -> Result<Comtrade, ComtradeError> {
+        if let Some(i) = analog.iter().find(|i| **i >= self.analog_channels.len()) {
+            return Err(ComtradeError::AnalogChannelNotFound(*i));
+        }
+        if let Some(i) = status.iter().find(|i| **i >= self.status_channels.len()) {
+            return Err(ComtradeError::StatusChannelNotFound(*i));
+        }
+
+        let keep_analog: Vec<usize> = (0..self.analog_channels.len())
+            .filter(|i| !analog.contains(i))
+            .collect();
+        let keep_status: Vec<usize> = (0..self.status_channels.len())
+            .filter(|i| !status.contains(i))
+            .collect();
+
+        self.select_channels(&keep_analog, &keep_status)
+    }
+
+    /// New record with only the channels whose config satisfies the predicates,
+    /// keeping their original order. The remaining channels are reindexed from 1.
+    ///
+    /// ```rust,no_run
+    /// # fn example(record: comtrade::Comtrade) {
+    /// // Drop calculated angle channels and unused status inputs.
+    /// let shareable = record.filter_channels(
+    ///     |analog| !analog.name.ends_with("Angle"),
+    ///     |status| status.name != "Off",
+    /// );
+    /// # }
+    /// ```
+    pub fn filter_channels(
+        &self,
+        analog: impl Fn(&AnalogConfig) -> bool,
+        status: impl Fn(&StatusConfig) -> bool,
+    ) -> Comtrade {
+        let keep_analog: Vec<usize> = self
+            .analog_channels
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| analog(&c.config))
+            .map(|(i, _)| i)
+            .collect();
+        let keep_status: Vec<usize> = self
+            .status_channels
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| status(&c.config))
+            .map(|(i, _)| i)
+            .collect();
+
+        // Indices all come from the channels themselves, so can't be out of bounds.
+        self.select_channels(&keep_analog, &keep_status).unwrap()
+    }
+}
@@ -0,0 +1,168 @@
+use std::ops::Range;
+
+use crate::error::ComtradeError;
+use crate::window::seconds_to_duration;
+use crate::{AnalogChannel, Comtrade, SamplingRate, StatusChannel};
+
+/// Portion of a record to keep when slicing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SliceRange {
+    /// 0-indexed sample positions (not sample numbers), end exclusive.
+    Samples(Range<usize>),
+
+    /// Times in seconds relative to the start of the record, end exclusive.
+    Time(Range<f64>),
+}
+
+impl From<Range<usize>> for SliceRange {
+    fn from(range: Range<usize>) -> Self {
+        SliceRange::Samples(range)
+    }
+}
+
+impl From<Range<f64>> for SliceRange {
+    fn from(range: Range<f64>) -> Self {
+        SliceRange::Time(range)
+    }
+}
+
+/// Work out the sampling rate segments covering the sample numbers `first..=last`
+/// once they've been renumbered to start from 1.
+fn slice_sampling_rates(rates: &[SamplingRate], first: u32, last: u32) -> Vec<SamplingRate> {
+    let mut segment_start = 1;
+    let mut sliced = Vec::new();
+
+    for rate in rates {
+        let segment_end = rate.end_sample_number;
+        if segment_end >= first && segment_start <= last {
+            sliced.push(SamplingRate {
+                rate_hz: rate.rate_hz,
+                end_sample_number: segment_end.min(last) - first + 1,
+            });
+        }
+        segment_start = segment_end + 1;
+    }
+
+    sliced
+}
+
+impl Comtrade {
+    /// Cut out part of the record as a new, self-consistent record.
+    ///
+    /// Samples are renumbered from 1, timestamps are rebased so the first sample
+    /// of the slice is at time zero and `start_time` is moved forward to match.
+    /// Sampling rate segments are truncated to the slice and the analog channel
+    /// min/max values are recalculated, so the result can be written back out as
+    /// is. The trigger time is an absolute time so is left alone, even if it falls
+    /// outside the slice.
+    ///
+    /// ```rust,no_run
+    /// # fn example(record: comtrade::Comtrade) -> Result<(), comtrade::ComtradeError> {
+    /// // 100ms either side of the trigger.
+    /// let trigger = record.trigger_offset();
+    /// let around_trigger = record.slice(trigger - 0.1..trigger + 0.1)?;
+    ///
+    /// // First 1000 samples.
+    /// let start = record.slice(0..1000)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn slice(&self, range: impl Into<SliceRange>) -> Result<Comtrade, ComtradeError> {
+        let len = self.timestamps.len();
+        let samples = match range.into() {
+            SliceRange::Samples(samples) => {
+                if samples.start > samples.end || samples.end > len {
+                    return Err(ComtradeError::SampleRangeOutOfBounds {
+                        start: samples.start,
+                        end: samples.end,
+                        len,
+                    });
+                }
+                samples
+            }
+            SliceRange::Time(time) => {
+                let start = self.timestamps.partition_point(|t| *t < time.start);
+                let end = self.timestamps.partition_point(|t| *t < time.end);
+                start..end.max(start)
+            }
+        };
+
+        if samples.is_empty() {
+            return Err(ComtradeError::EmptyRange);
+        }
+
+        let offset = self.timestamps[samples.start];
+        let first_sample_number = self.sample_numbers[samples.start];
+        let last_sample_number = self.sample_numbers[samples.end - 1];
+
+        let analog_channels = self
+            .analog_channels
+            .iter()
+            .map(|c| {
+                let mut channel = AnalogChannel {
+                    config: c.config.clone(),
+                    data: c.data[samples.clone()].to_vec(),
+                };
+                channel.recalculate_limits(&self.data_format);
+                channel
+            })
+            .collect();
+
+        let status_channels = self
+            .status_channels
+            .iter()
+            .map(|c| StatusChannel {
+                config: c.config.clone(),
+                data: c.data[samples.clone()].to_vec(),
+            })
+            .collect();
+
+        Ok(Comtrade {
+            sample_numbers: (1..=samples.len() as u32).collect(),
+            timestamps: self.timestamps[samples.clone()]
+                .iter()
+                .map(|t| t - offset)
+                .collect(),
+            analog_channels,
+            status_channels,
+            sampling_rates: slice_sampling_rates(
+                &self.sampling_rates,
+                first_sample_number,
+                last_sample_number,
+            ),
+            start_time: self.start_time + seconds_to_duration(offset),
+            ..self.clone_without_data()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(rate_hz: f64, end_sample_number: u32) -> SamplingRate {
+        SamplingRate {
+            rate_hz,
+            end_sample_number,
+        }
+    }
+
+    #[test]
+    fn slice_sampling_rates_within_single_segment() {
+        let rates = vec![rate(1000.0, 100), rate(500.0, 200)];
+        assert_eq!(slice_sampling_rates(&rates, 11, 20), vec![rate(1000.0, 10)]);
+        assert_eq!(
+            slice_sampling_rates(&rates, 150, 200),
+            vec![rate(500.0, 51)]
+        );
+    }
+
+    #[test]
+    fn slice_sampling_rates_across_segments() {
+        let rates = vec![rate(1000.0, 100), rate(500.0, 200), rate(250.0, 300)];
+        assert_eq!(
+            slice_sampling_rates(&rates, 91, 210),
+            vec![rate(1000.0, 10), rate(500.0, 110), rate(250.0, 120)]
+        );
+    }
+}
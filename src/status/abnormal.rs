@@ -0,0 +1,75 @@
+use crate::Comtrade;
+
+/// How long a status channel spent away from its normal status value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbnormalStatus {
+    /// 0-indexed position of the channel in `status_channels`.
+    pub channel: usize,
+    pub name: String,
+
+    pub ever_abnormal: bool,
+
+    /// Whether the channel was already abnormal at the first sample, e.g. a
+    /// blocking signal which was asserted throughout.
+    pub abnormal_at_start: bool,
+
+    /// Total time in seconds the channel was abnormal. Each sample's value holds
+    /// until the next sample, so an abnormal value at the last sample adds nothing.
+    pub abnormal_duration: f64,
+
+    /// Time of the first abnormal sample, in seconds relative to the start of the
+    /// record.
+    pub first_abnormal: Option<f64>,
+}
+
+impl Comtrade {
+    /// Summarise when a status channel differed from its `normal_status_value`.
+    /// `None` if there's no such channel.
+    pub fn abnormal_status(&self, channel: usize) -> Option<AbnormalStatus> {
+        let status = self.status_channels.get(channel)?;
+        let normal = status.config.normal_status_value;
+
+        let mut abnormal_duration = 0.0;
+        let mut first_abnormal = None;
+        for (n, value) in status.data.iter().enumerate() {
+            if *value == normal {
+                continue;
+            }
+            first_abnormal.get_or_insert(self.timestamps[n]);
+            if let Some(next) = self.timestamps.get(n + 1) {
+                abnormal_duration += next - self.timestamps[n];
+            }
+        }
+
+        Some(AbnormalStatus {
+            channel,
+            name: status.config.name.trim().to_string(),
+            ever_abnormal: first_abnormal.is_some(),
+            abnormal_at_start: status.data.first().is_some_and(|v| *v != normal),
+            abnormal_duration,
+            first_abnormal,
+        })
+    }
+
+    /// Abnormal status summary for every status channel, in channel order.
+    pub fn abnormal_statuses(&self) -> Vec<AbnormalStatus> {
+        (0..self.status_channels.len())
+            .filter_map(|channel| self.abnormal_status(channel))
+            .collect()
+    }
+
+    /// Status channels which were abnormal at some point in the record, in the
+    /// order they first went abnormal. For protection relay records these are the
+    /// elements which picked up or operated.
+    pub fn operated_elements(&self) -> Vec<AbnormalStatus> {
+        let mut operated: Vec<AbnormalStatus> = self
+            .abnormal_statuses()
+            .into_iter()
+            .filter(|s| s.ever_abnormal)
+            .collect();
+        // Stable, so channel order is kept for channels changing at the same time.
+        let first = |s: &AbnormalStatus| s.first_abnormal.unwrap_or(f64::INFINITY);
+        operated.sort_by(|a, b| first(a).total_cmp(&first(b)));
+        operated
+    }
+}
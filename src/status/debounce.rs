@@ -0,0 +1,154 @@
+use crate::error::ComtradeError;
+use crate::Comtrade;
+
+/// A status channel changing value more often than it plausibly should, usually
+/// due to a bouncing contact or noisy input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chatter {
+    /// 0-indexed position of the channel in `status_channels`.
+    pub channel: usize,
+    pub name: String,
+
+    /// Number of transitions over the whole record.
+    pub transitions: usize,
+
+    /// Transitions per second in the busiest window.
+    pub peak_rate: f64,
+
+    /// Start of the busiest window, at the first transition in it, in seconds
+    /// relative to the start of the record.
+    pub peak_start: f64,
+}
+
+/// Replace runs of values lasting less than `min_pulse_width` seconds with the
+/// value before them. The first and last runs are kept as their true length
+/// isn't known. Returns the number of runs replaced.
+fn debounce(timestamps: &[f64], data: &mut [u8], min_pulse_width: f64) -> usize {
+    let mut removed = 0;
+    let mut state = match data.first() {
+        Some(first) => *first,
+        None => return 0,
+    };
+
+    let mut start = 0;
+    while start < data.len() {
+        let value = data[start];
+        let end = (start..data.len())
+            .find(|n| data[*n] != value)
+            .unwrap_or(data.len());
+
+        if value != state {
+            if end < data.len() && timestamps[end] - timestamps[start] < min_pulse_width {
+                data[start..end].iter_mut().for_each(|v| *v = state);
+                removed += 1;
+            } else {
+                state = value;
+            }
+        }
+        start = end;
+    }
+    removed
+}
+
+impl Comtrade {
+    /// Remove pulses shorter than `min_pulse_width` seconds from a status channel,
+    /// e.g. from a bouncing contact, by holding the value from before each pulse.
+    /// A bouncing change is moved to the start of the first run long enough to
+    /// count. Returns the number of pulses removed.
+    pub fn debounce_status(
+        &mut self,
+        channel: usize,
+        min_pulse_width: f64,
+    ) -> Result<usize, ComtradeError> {
+        let status = self
+            .status_channels
+            .get_mut(channel)
+            .ok_or(ComtradeError::StatusChannelNotFound(channel))?;
+        Ok(debounce(
+            &self.timestamps,
+            &mut status.data,
+            min_pulse_width,
+        ))
+    }
+
+    /// Debounce every status channel. Returns the total number of pulses removed.
+    pub fn debounce_all_status(&mut self, min_pulse_width: f64) -> usize {
+        self.status_channels
+            .iter_mut()
+            .map(|status| debounce(&self.timestamps, &mut status.data, min_pulse_width))
+            .sum()
+    }
+
+    /// Status channels with more than `max_rate` transitions per second within a
+    /// window of `window` seconds at any point in the record.
+    pub fn chattering_channels(
+        &self,
+        max_rate: f64,
+        window: f64,
+    ) -> Result<Vec<Chatter>, ComtradeError> {
+        if !window.is_finite() || window <= 0.0 {
+            return Err(ComtradeError::InvalidWindow(window));
+        }
+        let mut chatter = Vec::new();
+        for channel in 0..self.status_channels.len() {
+            let times: Vec<f64> = self
+                .status_transitions(channel)
+                .iter()
+                .map(|t| t.time)
+                .collect();
+
+            // Busiest window starting at a transition.
+            let mut peak = (0, 0.0);
+            for (i, start) in times.iter().enumerate() {
+                let count = times[i..].partition_point(|t| *t < start + window);
+                if count > peak.0 {
+                    peak = (count, *start);
+                }
+            }
+
+            let peak_rate = peak.0 as f64 / window;
+            if peak_rate > max_rate {
+                chatter.push(Chatter {
+                    channel,
+                    name: self.status_channels[channel].config.name.trim().to_string(),
+                    transitions: times.len(),
+                    peak_rate,
+                    peak_start: peak.1,
+                });
+            }
+        }
+        Ok(chatter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamps(len: usize) -> Vec<f64> {
+        (0..len).map(|n| n as f64 * 0.001).collect()
+    }
+
+    #[test]
+    fn debounce_removes_short_pulses() {
+        let mut data = vec![0, 0, 1, 0, 0, 1, 1, 1, 1, 0, 0, 0];
+        let removed = debounce(&timestamps(12), &mut data, 0.002);
+        assert_eq!(removed, 1);
+        assert_eq!(data, vec![0, 0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn debounce_keeps_transition_time_after_bounces() {
+        let mut data = vec![0, 0, 1, 0, 1, 0, 1, 1, 1, 1, 1, 1];
+        let removed = debounce(&timestamps(12), &mut data, 0.003);
+        assert_eq!(removed, 2);
+        assert_eq!(data, vec![0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn debounce_keeps_first_and_last_runs() {
+        let mut data = vec![1, 0, 0, 0, 0, 1];
+        assert_eq!(debounce(&timestamps(6), &mut data, 0.002), 0);
+        assert_eq!(data, vec![1, 0, 0, 0, 0, 1]);
+    }
+}
@@ -0,0 +1,11 @@
+//! Sequence of events and other analysis of status channels.
+
+mod abnormal;
+mod debounce;
+mod soe;
+mod timing;
+
+pub use abnormal::AbnormalStatus;
+pub use debounce::Chatter;
+pub use soe::StatusTransition;
+pub use timing::{StatusRoles, TimingOptions, TimingReport};
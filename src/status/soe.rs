@@ -0,0 +1,105 @@
+use chrono::NaiveDateTime;
+use regex::Regex;
+
+use crate::query::{collapse_whitespace, glob_to_regex};
+use crate::{Comtrade, StatusConfig};
+
+/// A status channel changing value, i.e. one entry in a sequence of events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusTransition {
+    /// 0-indexed position of the channel in `status_channels`.
+    pub channel: usize,
+    pub name: String,
+
+    /// 0-indexed position of the first sample with the new value.
+    pub sample: usize,
+
+    /// Time of the first sample with the new value, in seconds relative to the
+    /// start of the record.
+    pub time: f64,
+    pub absolute_time: NaiveDateTime,
+
+    /// Seconds after the trigger, negative for changes before it.
+    pub time_from_trigger: f64,
+
+    /// Value before and after the change.
+    pub from: u8,
+    pub to: u8,
+
+    /// Whether the channel changed away from its normal status value, e.g. a
+    /// contact closing on a normally open input.
+    pub abnormal: bool,
+}
+
+impl StatusTransition {
+    /// Whether the channel went from 0 to 1.
+    pub fn is_rising(&self) -> bool {
+        self.from == 0 && self.to == 1
+    }
+}
+
+impl Comtrade {
+    /// Every change in value of every status channel, sorted by time. Changes at
+    /// the same sample are in channel order. The value of the first sample isn't
+    /// a change.
+    pub fn sequence_of_events(&self) -> Vec<StatusTransition> {
+        self.transitions_where(|_| true)
+    }
+
+    /// Sequence of events for the status channels whose name matches the regular
+    /// expression, normalised like [`Comtrade::status_channels_matching`].
+    pub fn sequence_of_events_matching(&self, pattern: &Regex) -> Vec<StatusTransition> {
+        self.transitions_where(|c| pattern.is_match(&collapse_whitespace(&c.name)))
+    }
+
+    /// Sequence of events for the status channels whose name matches a glob such
+    /// as `Ph * OP`.
+    pub fn sequence_of_events_matching_glob(&self, glob: &str) -> Vec<StatusTransition> {
+        self.sequence_of_events_matching(&glob_to_regex(glob))
+    }
+
+    /// Changes in value of a single status channel, in order. Empty if there's no
+    /// such channel.
+    pub fn status_transitions(&self, channel: usize) -> Vec<StatusTransition> {
+        let status = match self.status_channels.get(channel) {
+            Some(status) => status,
+            None => return Vec::new(),
+        };
+        let trigger = self.trigger_offset();
+
+        status
+            .data
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| pair[0] != pair[1])
+            .map(|(n, pair)| {
+                let sample = n + 1;
+                let time = self.timestamps[sample];
+                StatusTransition {
+                    channel,
+                    name: status.config.name.trim().to_string(),
+                    sample,
+                    time,
+                    absolute_time: self.absolute_time(time),
+                    time_from_trigger: time - trigger,
+                    from: pair[0],
+                    to: pair[1],
+                    abnormal: pair[1] != status.config.normal_status_value,
+                }
+            })
+            .collect()
+    }
+
+    fn transitions_where(&self, include: impl Fn(&StatusConfig) -> bool) -> Vec<StatusTransition> {
+        let mut transitions: Vec<StatusTransition> = self
+            .status_channels
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| include(&c.config))
+            .flat_map(|(i, _)| self.status_transitions(i))
+            .collect();
+        // Stable, so channel order is kept within a sample.
+        transitions.sort_by_key(|t| t.sample);
+        transitions
+    }
+}
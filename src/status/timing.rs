@@ -0,0 +1,194 @@
+use regex::Regex;
+
+use crate::analysis::RmsWindow;
+use crate::error::ComtradeError;
+use crate::query::{collapse_whitespace, glob_to_regex};
+use crate::status::StatusTransition;
+use crate::Comtrade;
+
+/// Which status channels play each part in clearing a fault, as globs matched
+/// against channel names like [`Comtrade::status_channels_matching_glob`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StatusRoles {
+    /// Protection element pickup or start signals.
+    pub pickup: Vec<String>,
+
+    /// Trip outputs to the breaker.
+    pub trip: Vec<String>,
+
+    /// Breaker auxiliary contacts which are closed when the breaker is closed.
+    pub breaker_a: Vec<String>,
+
+    /// Breaker auxiliary contacts which are closed when the breaker is open.
+    pub breaker_b: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingOptions {
+    pub roles: StatusRoles,
+
+    /// Phase current channels (0-indexed positions in `analog_channels`).
+    pub currents: Vec<usize>,
+
+    /// RMS current, in the units the channels were recorded in, below which the
+    /// current counts as interrupted.
+    pub interruption_threshold: f64,
+
+    /// Time of fault inception in seconds relative to the start of the record,
+    /// e.g. from [`Comtrade::classify_fault`]. Defaults to the trigger.
+    pub fault_inception: Option<f64>,
+}
+
+/// Times of the stages of clearing a fault, in seconds after fault inception.
+/// Each is `None` if it didn't happen within the record.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingReport {
+    /// Time of fault inception in seconds relative to the start of the record.
+    pub fault_inception: f64,
+
+    /// First pickup signal asserting.
+    pub pickup: Option<f64>,
+
+    /// First trip signal asserting.
+    pub trip: Option<f64>,
+
+    /// First indication of the breaker opening after the trip (or after
+    /// inception without a trip): an `a` contact opening or `b` contact closing.
+    pub breaker_open: Option<f64>,
+
+    /// Current in all the phases dropping below the interruption threshold after
+    /// the trip (or after inception without a trip).
+    pub current_interruption: Option<f64>,
+}
+
+impl TimingReport {
+    /// Relay operating time from pickup to trip.
+    pub fn pickup_to_trip(&self) -> Option<f64> {
+        Some(self.trip? - self.pickup?)
+    }
+
+    /// Breaker auxiliary contact time from trip to the breaker indicating open.
+    pub fn trip_to_breaker_open(&self) -> Option<f64> {
+        Some(self.breaker_open? - self.trip?)
+    }
+
+    /// Breaker interrupting time from trip to the current stopping.
+    pub fn trip_to_interruption(&self) -> Option<f64> {
+        Some(self.current_interruption? - self.trip?)
+    }
+}
+
+fn role_patterns(globs: &[String]) -> Vec<Regex> {
+    globs.iter().map(|g| glob_to_regex(g)).collect()
+}
+
+impl Comtrade {
+    /// Transitions of the status channels matching any of the globs.
+    fn role_transitions(&self, globs: &[String]) -> Vec<StatusTransition> {
+        let patterns = role_patterns(globs);
+        self.sequence_of_events()
+            .into_iter()
+            .filter(|t| {
+                let name = collapse_whitespace(&t.name);
+                patterns.iter().any(|p| p.is_match(&name))
+            })
+            .collect()
+    }
+
+    /// Time the first status channel playing a role asserts.
+    pub(crate) fn first_assertion(&self, globs: &[String]) -> Option<f64> {
+        self.role_transitions(globs)
+            .iter()
+            .find(|t| t.abnormal)
+            .map(|t| t.time)
+    }
+
+    /// First indication of the breaker opening at or after `after`: an `a`
+    /// contact opening or a `b` contact closing.
+    pub(crate) fn breaker_open_after(&self, roles: &StatusRoles, after: f64) -> Option<f64> {
+        self.role_transitions(&roles.breaker_a)
+            .iter()
+            .filter(|t| t.to == 0)
+            .chain(
+                self.role_transitions(&roles.breaker_b)
+                    .iter()
+                    .filter(|t| t.to == 1),
+            )
+            .map(|t| t.time)
+            .filter(|t| *t >= after)
+            .fold(None, |first: Option<f64>, t| {
+                Some(first.map_or(t, |f| f.min(t)))
+            })
+    }
+
+    /// First time at or after `after` that the RMS current in every channel has
+    /// fallen below the threshold, pinned down to the sample after the last one
+    /// above the threshold.
+    fn current_interruption(
+        &self,
+        currents: &[usize],
+        threshold: f64,
+        after: f64,
+    ) -> Result<Option<f64>, ComtradeError> {
+        let rms = currents
+            .iter()
+            .map(|c| self.rms(*c, RmsWindow::Cycles(1.0)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let first = match rms.first() {
+            Some(first) => first,
+            None => return Ok(None),
+        };
+
+        let below = first.iter().position(|(t, _)| {
+            t >= after
+                && rms
+                    .iter()
+                    .all(|series| series.value_at(t).is_some_and(|v| *v < threshold))
+        });
+        let window_end = match below {
+            Some(n) => first.timestamps[n],
+            None => return Ok(None),
+        };
+
+        let end = self.timestamps.partition_point(|t| *t <= window_end);
+        let mut last_above: Option<usize> = None;
+        for channel in currents {
+            let data = self.analog_data(*channel)?;
+            if let Some(n) = (0..end).rev().find(|n| data[*n].abs() >= threshold) {
+                last_above = Some(last_above.map_or(n, |l| l.max(n)));
+            }
+        }
+        let interrupted = match last_above {
+            Some(n) => (n + 1).min(end - 1),
+            None => 0,
+        };
+        Ok(Some(self.timestamps[interrupted].max(after)))
+    }
+
+    /// Time the protection and breaker took to clear a fault, from the status
+    /// channels playing each role and the phase currents.
+    pub fn timing_report(&self, options: &TimingOptions) -> Result<TimingReport, ComtradeError> {
+        let inception = options
+            .fault_inception
+            .unwrap_or_else(|| self.trigger_offset());
+        let pickup = self.first_assertion(&options.roles.pickup);
+        let trip = self.first_assertion(&options.roles.trip);
+        let operating_from = trip.unwrap_or(inception);
+        let breaker_open = self.breaker_open_after(&options.roles, operating_from);
+
+        let current_interruption = self.current_interruption(
+            &options.currents,
+            options.interruption_threshold,
+            operating_from,
+        )?;
+
+        let relative = |time: Option<f64>| time.map(|t| t - inception);
+        Ok(TimingReport {
+            fault_inception: inception,
+            pickup: relative(pickup),
+            trip: relative(trip),
+            breaker_open: relative(breaker_open),
+            current_interruption: relative(current_interruption),
+        })
+    }
+}
@@ -0,0 +1,236 @@
+use std::ops::Range;
+
+use chrono::{Duration, NaiveDateTime};
+
+use crate::{AnalogConfig, Comtrade, StatusConfig};
+
+/// How to calculate a value between two samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    /// Take the value of the sample closest in time.
+    Nearest,
+
+    /// Linearly interpolate between the samples either side.
+    Linear,
+}
+
+pub(crate) fn duration_to_seconds(duration: Duration) -> f64 {
+    match duration.num_nanoseconds() {
+        Some(ns) => ns as f64 * 1e-9,
+        // Only happens for durations of hundreds of years, where the loss of
+        // sub-millisecond precision doesn't matter.
+        None => duration.num_milliseconds() as f64 * 1e-3,
+    }
+}
+
+pub(crate) fn seconds_to_duration(seconds: f64) -> Duration {
+    Duration::nanoseconds((seconds * 1e9).round() as i64)
+}
+
+impl Comtrade {
+    /// Time of the trigger in seconds relative to the start of the record, i.e. in
+    /// the same time base as `timestamps`. Negative if the trigger is before the
+    /// first sample.
+    pub fn trigger_offset(&self) -> f64 {
+        duration_to_seconds(self.trigger_time - self.start_time)
+    }
+
+    /// Convert an absolute time into seconds relative to the start of the record.
+    pub fn time_since_start(&self, time: NaiveDateTime) -> f64 {
+        duration_to_seconds(time - self.start_time)
+    }
+
+    /// Absolute time of a time given in seconds relative to the start of the record.
+    pub fn absolute_time(&self, time: f64) -> NaiveDateTime {
+        self.start_time + seconds_to_duration(time)
+    }
+
+    /// Absolute time at which the sample at `index` (0-indexed, not the sample
+    /// number) was taken.
+    pub fn sample_time(&self, index: usize) -> Option<NaiveDateTime> {
+        self.timestamps.get(index).map(|t| self.absolute_time(*t))
+    }
+
+    /// Index of the sample closest in time to `time` (seconds relative to start of
+    /// record). Times outside the record are clamped to the first or last sample.
+    pub fn sample_index_at_time(&self, time: f64) -> Option<usize> {
+        if self.timestamps.is_empty() {
+            return None;
+        }
+
+        let after = self.timestamps.partition_point(|t| *t < time);
+        if after == 0 {
+            return Some(0);
+        }
+        if after == self.timestamps.len() {
+            return Some(after - 1);
+        }
+
+        let before = after - 1;
+        if time - self.timestamps[before] <= self.timestamps[after] - time {
+            Some(before)
+        } else {
+            Some(after)
+        }
+    }
+
+    /// Value of an analog channel at the given time in seconds relative to the start
+    /// of the record. `channel` is the 0-indexed position in `analog_channels`.
+    ///
+    /// Returns `None` if the channel doesn't exist or the time lies outside the record.
+    pub fn value_at_time(
+        &self,
+        channel: usize,
+        time: f64,
+        interpolation: Interpolation,
+    ) -> Option<f64> {
+        let data = &self.analog_channels.get(channel)?.data;
+        let first = *self.timestamps.first()?;
+        let last = *self.timestamps.last()?;
+        if time < first || time > last {
+            return None;
+        }
+
+        match interpolation {
+            Interpolation::Nearest => data.get(self.sample_index_at_time(time)?).copied(),
+            Interpolation::Linear => {
+                let after = self.timestamps.partition_point(|t| *t < time);
+                if self.timestamps[after] == time || after == 0 {
+                    return data.get(after).copied();
+                }
+
+                let before = after - 1;
+                let (t0, t1) = (self.timestamps[before], self.timestamps[after]);
+                let (v0, v1) = (*data.get(before)?, *data.get(after)?);
+                Some(v0 + (v1 - v0) * (time - t0) / (t1 - t0))
+            }
+        }
+    }
+
+    /// Value of a status channel at the given time in seconds relative to the start
+    /// of the record. Status values hold until the next sample, so this is the value
+    /// of the last sample taken at or before `time`.
+    pub fn status_at_time(&self, channel: usize, time: f64) -> Option<u8> {
+        let data = &self.status_channels.get(channel)?.data;
+        let after = self.timestamps.partition_point(|t| *t <= time);
+        if after == 0 {
+            return None;
+        }
+        data.get(after - 1).copied()
+    }
+
+    /// Borrowed view of all samples with timestamps in `start..=end` (seconds relative
+    /// to the start of the record).
+    pub fn window(&self, start: f64, end: f64) -> RecordWindow<'_> {
+        let first = self.timestamps.partition_point(|t| *t < start);
+        let last = self.timestamps.partition_point(|t| *t <= end).max(first);
+        RecordWindow {
+            record: self,
+            samples: first..last,
+        }
+    }
+
+    /// Borrowed view of the samples at the given 0-indexed positions.
+    pub fn window_samples(&self, samples: Range<usize>) -> RecordWindow<'_> {
+        let len = self.timestamps.len();
+        let end = samples.end.min(len);
+        RecordWindow {
+            record: self,
+            samples: samples.start.min(end)..end,
+        }
+    }
+
+    /// Window covering the `duration` seconds immediately before the trigger.
+    pub fn pre_fault(&self, duration: f64) -> RecordWindow<'_> {
+        let trigger = self.trigger_offset();
+        let start = self.timestamps.partition_point(|t| *t < trigger - duration);
+        let end = self.timestamps.partition_point(|t| *t < trigger);
+        self.window_samples(start..end)
+    }
+
+    /// Window covering the `duration` seconds starting at the trigger.
+    pub fn post_fault(&self, duration: f64) -> RecordWindow<'_> {
+        let trigger = self.trigger_offset();
+        let start = self.timestamps.partition_point(|t| *t < trigger);
+        let end = self.timestamps.partition_point(|t| *t < trigger + duration);
+        self.window_samples(start..end)
+    }
+}
+
+/// A view of a contiguous range of samples across every channel of a record.
+#[derive(Debug, Clone)]
+pub struct RecordWindow<'a> {
+    record: &'a Comtrade,
+    samples: Range<usize>,
+}
+
+impl<'a> RecordWindow<'a> {
+    /// The record this window is looking at.
+    pub fn record(&self) -> &'a Comtrade {
+        self.record
+    }
+
+    /// 0-indexed positions of the samples in the window within the full record.
+    pub fn samples(&self) -> Range<usize> {
+        self.samples.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn sample_numbers(&self) -> &'a [u32] {
+        &self.record.sample_numbers[self.samples.clone()]
+    }
+
+    /// Timestamps of the samples in seconds relative to the start of the record.
+    pub fn timestamps(&self) -> &'a [f64] {
+        &self.record.timestamps[self.samples.clone()]
+    }
+
+    /// Time of the first sample in the window, relative to the start of the record.
+    pub fn start(&self) -> Option<f64> {
+        self.timestamps().first().copied()
+    }
+
+    /// Time of the last sample in the window, relative to the start of the record.
+    pub fn end(&self) -> Option<f64> {
+        self.timestamps().last().copied()
+    }
+
+    /// Data for the analog channel at 0-indexed position `channel`.
+    pub fn analog(&self, channel: usize) -> Option<&'a [f64]> {
+        self.record
+            .analog_channels
+            .get(channel)
+            .map(|c| &c.data[self.samples.clone()])
+    }
+
+    /// Data for the status channel at 0-indexed position `channel`.
+    pub fn status(&self, channel: usize) -> Option<&'a [u8]> {
+        self.record
+            .status_channels
+            .get(channel)
+            .map(|c| &c.data[self.samples.clone()])
+    }
+
+    /// Config and windowed data for every analog channel.
+    pub fn analog_channels(&self) -> impl Iterator<Item = (&'a AnalogConfig, &'a [f64])> + '_ {
+        self.record
+            .analog_channels
+            .iter()
+            .map(move |c| (&c.config, &c.data[self.samples.clone()]))
+    }
+
+    /// Config and windowed data for every status channel.
+    pub fn status_channels(&self) -> impl Iterator<Item = (&'a StatusConfig, &'a [u8])> + '_ {
+        self.record
+            .status_channels
+            .iter()
+            .map(move |c| (&c.config, &c.data[self.samples.clone()]))
+    }
+}
@@ -1,11 +1,136 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::num::NonZeroUsize;
+use std::path::Path;
+
 use float_cmp::approx_eq;
 
-use comtrade::Comtrade;
+use comtrade::{
+    AnalogChannel, AnalogConfig, AnalogScalingMode, Comtrade, ComtradeParserBuilder, SamplingRate,
+    StatusChannel, StatusConfig,
+};
 
 pub const SAMPLE_COMTRADE_DIR: &str = "./tests/comtrade_files";
 pub const MINUTE: i32 = 60;
 pub const HOUR: i32 = MINUTE * 60;
 
+/// Parse a `.cfg` / `.dat` pair from the sample COMTRADE directory.
+pub fn load_comtrade(cfg_name: &str, dat_name: &str) -> Comtrade {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg_file = BufReader::new(File::open(dir.join(cfg_name)).expect("unable to find cfg file"));
+    let dat_file = BufReader::new(File::open(dir.join(dat_name)).expect("unable to find dat file"));
+
+    ComtradeParserBuilder::new()
+        .cfg_file(cfg_file)
+        .dat_file(dat_file)
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files")
+}
+
+/// Description of a synthetic analog channel: name, phase, circuit component,
+/// units and real (i.e. already scaled) values.
+pub type SyntheticAnalog<'a> = (&'a str, &'a str, &'a str, &'a str, Vec<f64>);
+
+/// Build a record sampled at a single fixed rate from channel data.
+pub fn synthetic_comtrade(
+    rate_hz: f64,
+    line_frequency: f64,
+    analog: Vec<SyntheticAnalog>,
+    status: Vec<(&str, Vec<u8>)>,
+) -> Comtrade {
+    let num_samples = analog
+        .first()
+        .map(|a| a.4.len())
+        .or_else(|| status.first().map(|s| s.1.len()))
+        .unwrap_or(0);
+
+    Comtrade {
+        station_name: "station".to_string(),
+        recording_device_id: "device".to_string(),
+        line_frequency,
+        sampling_rates: vec![SamplingRate {
+            rate_hz,
+            end_sample_number: num_samples as u32,
+        }],
+        sample_numbers: (1..=num_samples as u32).collect(),
+        timestamps: (0..num_samples).map(|i| i as f64 / rate_hz).collect(),
+        analog_channels: analog
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, phase, component, units, data))| AnalogChannel {
+                config: AnalogConfig {
+                    index: NonZeroUsize::new(i + 1).unwrap(),
+                    name: name.to_string(),
+                    phase: phase.to_string(),
+                    circuit_component_being_monitored: component.to_string(),
+                    units: units.to_string(),
+                    min_value: -32767.0,
+                    max_value: 32767.0,
+                    multiplier: 1.0,
+                    offset_adder: 0.0,
+                    skew: 0.0,
+                    primary_factor: 1.0,
+                    secondary_factor: 1.0,
+                    scaling_mode: AnalogScalingMode::Primary,
+                },
+                data,
+            })
+            .collect(),
+        status_channels: status
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, data))| StatusChannel {
+                config: StatusConfig {
+                    index: NonZeroUsize::new(i + 1).unwrap(),
+                    name: name.to_string(),
+                    phase: "".to_string(),
+                    circuit_component_being_monitored: "".to_string(),
+                    normal_status_value: 0,
+                },
+                data,
+            })
+            .collect(),
+        ..Comtrade::default()
+    }
+}
+
+/// Samples of `magnitude * sqrt(2) * cos(2π * frequency * t + angle)`.
+pub fn sine_wave(
+    rate_hz: f64,
+    num_samples: usize,
+    frequency: f64,
+    magnitude: f64,
+    angle_degrees: f64,
+) -> Vec<f64> {
+    (0..num_samples)
+        .map(|i| {
+            let t = i as f64 / rate_hz;
+            magnitude
+                * std::f64::consts::SQRT_2
+                * (2.0 * std::f64::consts::PI * frequency * t + angle_degrees.to_radians()).cos()
+        })
+        .collect()
+}
+
+/// Sine wave which switches to a new RMS magnitude and angle at given samples, as
+/// `(first_sample, magnitude, angle_degrees)` steps in order. The first step
+/// should start at sample 0.
+pub fn stepped_sine_wave(
+    rate_hz: f64,
+    num_samples: usize,
+    frequency: f64,
+    steps: &[(usize, f64, f64)],
+) -> Vec<f64> {
+    let mut data = Vec::with_capacity(num_samples);
+    for (i, (start, magnitude, angle_degrees)) in steps.iter().enumerate() {
+        let end = steps.get(i + 1).map_or(num_samples, |s| s.0);
+        let wave = sine_wave(rate_hz, end, frequency, *magnitude, *angle_degrees);
+        data.extend_from_slice(&wave[*start..end]);
+    }
+    data
+}
+
 pub fn assert_comtrades_eq(left: &Comtrade, right: &Comtrade) {
     // Floating point comparisons need a special approximately equal rather than the
     // normal one, so we do that below. To not have to manually write out the rest of
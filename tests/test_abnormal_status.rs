@@ -0,0 +1,59 @@
+mod common;
+
+use common::{load_comtrade, synthetic_comtrade};
+
+#[test]
+fn it_summarises_abnormal_status_channels() {
+    let mut record = synthetic_comtrade(
+        1000.0,
+        50.0,
+        vec![],
+        vec![
+            ("Ph TOC 1 OP", vec![0, 0, 0, 1, 1, 1, 0, 0]),
+            ("GND TOC 1 OP", vec![0, 1, 1, 0, 0, 0, 0, 0]),
+            ("Off", vec![0; 8]),
+            ("52a", vec![1, 1, 1, 1, 1, 0, 0, 0]),
+        ],
+    );
+    // Breaker normally closed, opening at sample 5.
+    record.status_channels[3].config.normal_status_value = 1;
+
+    let toc = record.abnormal_status(0).unwrap();
+    assert_eq!(toc.name, "Ph TOC 1 OP");
+    assert!(toc.ever_abnormal);
+    assert!(!toc.abnormal_at_start);
+    assert!((toc.abnormal_duration - 0.003).abs() < 1e-12);
+    assert_eq!(toc.first_abnormal, Some(record.timestamps[3]));
+
+    let off = record.abnormal_status(2).unwrap();
+    assert!(!off.ever_abnormal);
+    assert_eq!(off.abnormal_duration, 0.0);
+    assert_eq!(off.first_abnormal, None);
+
+    // Open until the end of the record, the last sample adding nothing.
+    let breaker = record.abnormal_status(3).unwrap();
+    assert!((breaker.abnormal_duration - 0.002).abs() < 1e-12);
+
+    assert_eq!(record.abnormal_statuses().len(), 4);
+    assert_eq!(record.abnormal_status(4), None);
+
+    let operated: Vec<String> = record
+        .operated_elements()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    assert_eq!(operated, vec!["GND TOC 1 OP", "Ph TOC 1 OP", "52a"]);
+}
+
+#[test]
+fn it_summarises_real_relay_record() {
+    // Relay record with critical timestamps, where no protection elements operated.
+    let record = load_comtrade("real_1999_bin.cfg", "real_1999_bin.dat");
+    assert_eq!(record.timestamps.len(), 8000);
+    assert!(record.sampling_rates.is_empty());
+
+    let statuses = record.abnormal_statuses();
+    assert_eq!(statuses.len(), 64);
+    assert_eq!(statuses[0].name, "Ph TOC 1 OP");
+    assert!(record.operated_elements().is_empty());
+}
@@ -0,0 +1,52 @@
+use regex::Regex;
+
+mod common;
+
+use common::load_comtrade;
+
+#[test]
+fn it_finds_channels_by_normalised_name() {
+    let mut record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    record.analog_channels[1].config.name = "  IB     ".to_string();
+
+    let ia = record.analog_channel_by_name("ia").expect("IA not found");
+    assert_eq!(ia.config.index.get(), 1);
+
+    let ib = record.analog_channel_by_name("IB").expect("IB not found");
+    assert_eq!(ib.config.index.get(), 2);
+
+    let status = record
+        .status_channel_by_name(" 51n ")
+        .expect("51N not found");
+    assert_eq!(status.config.index.get(), 4);
+
+    assert!(record.analog_channel_by_name("IX").is_none());
+}
+
+#[test]
+fn it_finds_channels_by_pattern() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+
+    let phase_currents = record.analog_channels_matching_glob("i?");
+    assert_eq!(phase_currents.len(), 3);
+
+    let re = Regex::new("^51[A-C]$").unwrap();
+    let names: Vec<&str> = record
+        .status_channels_matching(&re)
+        .iter()
+        .map(|c| c.config.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["51A", "51B", "51C"]);
+}
+
+#[test]
+fn it_finds_channels_by_component_phase_and_units() {
+    let mut record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    record.analog_channels[0].config.phase = "A".to_string();
+
+    assert_eq!(record.analog_channels_by_component("line123").len(), 4);
+    assert_eq!(record.status_channels_by_component("Line123 ").len(), 4);
+    assert_eq!(record.analog_channels_by_phase("a", "Line123").len(), 1);
+    assert_eq!(record.analog_channels_by_units("A").len(), 4);
+    assert!(record.analog_channels_by_units("kA").is_empty());
+}
@@ -0,0 +1,103 @@
+use chrono::Duration;
+
+use comtrade::{Comtrade, ComtradeError, SamplingRate};
+
+mod common;
+
+use common::load_comtrade;
+
+#[test]
+fn it_joins_consecutive_records() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let parts = [
+        record.slice(0..10).unwrap(),
+        record.slice(10..25).unwrap(),
+        record.slice(25..40).unwrap(),
+    ];
+
+    let joined = Comtrade::concat(&parts).expect("unable to join records");
+
+    assert_eq!(joined.sample_numbers, record.sample_numbers);
+    assert_eq!(joined.start_time, record.start_time);
+    assert_eq!(joined.sampling_rates, record.sampling_rates);
+    for (t_joined, t_original) in joined.timestamps.iter().zip(record.timestamps.iter()) {
+        assert!((t_joined - t_original).abs() < 1e-9);
+    }
+    for (c_joined, c_original) in joined
+        .analog_channels
+        .iter()
+        .zip(record.analog_channels.iter())
+    {
+        assert_eq!(c_joined.data, c_original.data);
+    }
+    for (c_joined, c_original) in joined
+        .status_channels
+        .iter()
+        .zip(record.status_channels.iter())
+    {
+        assert_eq!(c_joined.data, c_original.data);
+    }
+}
+
+#[test]
+fn it_adds_sampling_rate_segments_when_rates_change() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let mut first = record.slice(0..20).unwrap();
+    let mut second = record.slice(20..40).unwrap();
+
+    // Pretend the second file was recorded at double the rate, starting one
+    // (faster) sample interval after the first file ends.
+    second.sampling_rates[0].rate_hz = 2400.0;
+    second.timestamps = (0..20).map(|i| i as f64 / 2400.0).collect();
+    second.start_time = first.start_time
+        + Duration::nanoseconds((19.0 / 1200.0 * 1e9) as i64)
+        + Duration::nanoseconds((1.0 / 2400.0 * 1e9) as i64);
+
+    first.append(&second).expect("unable to append record");
+
+    assert_eq!(
+        first.sampling_rates,
+        vec![
+            SamplingRate {
+                rate_hz: 1200.0,
+                end_sample_number: 20
+            },
+            SamplingRate {
+                rate_hz: 2400.0,
+                end_sample_number: 40
+            },
+        ]
+    );
+    assert_eq!(first.sample_numbers, (1..=40).collect::<Vec<u32>>());
+}
+
+#[test]
+fn it_rejects_records_which_dont_follow_on() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let first = record.slice(0..20).unwrap();
+
+    let gap = record.slice(22..40).unwrap();
+    assert!(matches!(
+        Comtrade::concat(&[first.clone(), gap]),
+        Err(ComtradeError::NotContiguous(_))
+    ));
+
+    let fewer_channels = record
+        .slice(20..40)
+        .unwrap()
+        .remove_channels(&[0], &[])
+        .unwrap();
+    assert_eq!(
+        Comtrade::concat(&[first.clone(), fewer_channels]),
+        Err(ComtradeError::MismatchedChannelCount)
+    );
+
+    let mut renamed = record.slice(20..40).unwrap();
+    renamed.status_channels[2].config.name = "52A".to_string();
+    assert_eq!(
+        Comtrade::concat(&[first, renamed]),
+        Err(ComtradeError::MismatchedStatusChannel(2))
+    );
+
+    assert_eq!(Comtrade::concat(&[]), Err(ComtradeError::NoRecords));
+}
@@ -0,0 +1,88 @@
+use comtrade::ComtradeError;
+
+mod common;
+
+use common::synthetic_comtrade;
+
+/// 1kHz status data with a contact which bounces for a few milliseconds when it
+/// closes at 10ms, and one which chatters throughout.
+fn bouncing_record() -> comtrade::Comtrade {
+    let mut bouncing = vec![0u8; 100];
+    for (n, value) in bouncing.iter_mut().enumerate().skip(10) {
+        *value = match n {
+            11 | 13 => 0,
+            _ => 1,
+        };
+    }
+    let chattering = (0..100).map(|n| ((n / 3) % 2) as u8).collect();
+    synthetic_comtrade(
+        1000.0,
+        50.0,
+        vec![],
+        vec![
+            ("TRIP", bouncing),
+            ("Noisy input", chattering),
+            ("Quiet", vec![0; 100]),
+        ],
+    )
+}
+
+#[test]
+fn it_debounces_status_channels() {
+    let mut record = bouncing_record();
+    assert_eq!(record.status_transitions(0).len(), 5);
+
+    let removed = record.debounce_status(0, 0.002).unwrap();
+    assert_eq!(removed, 2);
+    // The first make is itself a short pulse, so the contact closes at the start
+    // of the first run long enough to count.
+    let transitions = record.status_transitions(0);
+    assert_eq!(transitions.len(), 1);
+    assert_eq!(transitions[0].sample, 14);
+
+    assert_eq!(
+        record.debounce_status(3, 0.002),
+        Err(ComtradeError::StatusChannelNotFound(3))
+    );
+}
+
+#[test]
+fn it_debounces_all_channels() {
+    let mut record = bouncing_record();
+    // The noisy input's 3ms pulses are long enough to survive.
+    assert_eq!(record.debounce_all_status(0.0025), 2);
+    assert_eq!(record.status_transitions(1).len(), 33);
+}
+
+#[test]
+fn it_detects_chattering_channels() {
+    let record = bouncing_record();
+    let chatter = record.chattering_channels(100.0, 0.05).unwrap();
+    assert_eq!(chatter.len(), 1);
+
+    let noisy = &chatter[0];
+    assert_eq!(noisy.channel, 1);
+    assert_eq!(noisy.name, "Noisy input");
+    assert_eq!(noisy.transitions, 33);
+    assert!(
+        (noisy.peak_rate - 340.0).abs() < 1e-9,
+        "{}",
+        noisy.peak_rate
+    );
+    assert_eq!(noisy.peak_start, record.timestamps[3]);
+
+    // The bounce is brief, so only stands out over a short window.
+    let chatter = record.chattering_channels(500.0, 0.005).unwrap();
+    assert_eq!(
+        chatter.iter().map(|c| c.channel).collect::<Vec<_>>(),
+        vec![0]
+    );
+    assert_eq!(chatter[0].peak_start, record.timestamps[10]);
+
+    for window in [0.0, -0.05, f64::NAN] {
+        assert!(matches!(
+            record.chattering_channels(100.0, window),
+            Err(ComtradeError::InvalidWindow(_))
+        ));
+    }
+}
@@ -0,0 +1,113 @@
+use comtrade::{AnalogScalingMode, ComtradeError};
+
+mod common;
+
+use common::{load_comtrade, synthetic_comtrade};
+
+#[test]
+fn it_derives_residual_current_from_phase_currents() {
+    let mut record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let position = record.add_derived_channel("IR", "IA + IB + IC").unwrap();
+    assert_eq!(position, 4);
+
+    let derived = &record.analog_channels[4];
+    assert_eq!(derived.config.index.get(), 5);
+    assert_eq!(derived.config.name, "IR");
+    assert_eq!(derived.config.units, "A");
+    assert_eq!(derived.config.circuit_component_being_monitored, "Line123");
+    assert_eq!(derived.config.scaling_mode, AnalogScalingMode::Secondary);
+    assert_eq!(derived.config.primary_factor, 933.0);
+
+    for n in 0..record.timestamps.len() {
+        let sum: f64 = record.analog_channels[..3].iter().map(|c| c.data[n]).sum();
+        assert_eq!(derived.data[n], sum);
+    }
+}
+
+#[test]
+fn it_evaluates_expressions_with_precedence_and_brackets() {
+    let record = synthetic_comtrade(
+        1000.0,
+        50.0,
+        vec![
+            ("VA", "A", "Bus 1", "kV", vec![1.0, 2.0, 3.0]),
+            ("VB", "B", "Bus 1", "kV", vec![4.0, 5.0, 6.0]),
+            ("Line 1 IA", "A", "Line 1", "A", vec![10.0, 20.0, 30.0]),
+        ],
+        vec![],
+    );
+
+    let vab = record.derived_channel("VAB", "va - vb").unwrap();
+    assert_eq!(vab.data, vec![-3.0, -3.0, -3.0]);
+    assert_eq!(vab.config.units, "kV");
+    assert_eq!(vab.config.phase, "");
+    assert_eq!(vab.config.circuit_component_being_monitored, "Bus 1");
+
+    let scaled = record.derived_channel("X", "-(VA + 1) * 2 / 4").unwrap();
+    assert_eq!(scaled.data, vec![-1.0, -1.5, -2.0]);
+    assert_eq!(scaled.config.units, "kV");
+
+    let power = record.derived_channel("P", "VA * [Line 1 IA]").unwrap();
+    assert_eq!(power.data, vec![10.0, 40.0, 90.0]);
+    assert_eq!(power.config.units, "kV*A");
+}
+
+#[test]
+fn it_reports_invalid_expressions() {
+    let record = synthetic_comtrade(
+        1000.0,
+        50.0,
+        vec![
+            ("VA", "A", "Bus 1", "kV", vec![1.0]),
+            ("IA", "A", "Bus 1", "A", vec![1.0]),
+        ],
+        vec![],
+    );
+
+    assert_eq!(
+        record.derived_channel("X", "VA + VZ"),
+        Err(ComtradeError::AnalogChannelNameNotFound("VZ".to_string()))
+    );
+    assert_eq!(
+        record.derived_channel("X", "VA + IA"),
+        Err(ComtradeError::MismatchedUnits(
+            "kV".to_string(),
+            "A".to_string()
+        ))
+    );
+    assert!(matches!(
+        record.derived_channel("X", "(VA + 1"),
+        Err(ComtradeError::InvalidExpression { position: 7, .. })
+    ));
+    assert!(matches!(
+        record.derived_channel("X", "VA $ 2"),
+        Err(ComtradeError::InvalidExpression { position: 3, .. })
+    ));
+}
+
+#[test]
+fn it_converts_mixed_primary_and_secondary_channels_to_primary() {
+    let mut record = synthetic_comtrade(
+        1000.0,
+        50.0,
+        vec![
+            ("IA", "A", "Line 1", "A", vec![5.0, -2.5]),
+            ("IB", "B", "Line 1", "A", vec![1000.0, -500.0]),
+        ],
+        vec![],
+    );
+    // IA comes from a 1000/5 CT and is recorded in secondary amps.
+    let ia = &mut record.analog_channels[0].config;
+    ia.primary_factor = 1000.0;
+    ia.secondary_factor = 5.0;
+    ia.scaling_mode = AnalogScalingMode::Secondary;
+
+    let difference = record.derived_channel("IA - IB", "IA - IB").unwrap();
+    assert_eq!(difference.data, vec![0.0, 0.0]);
+    assert_eq!(difference.config.scaling_mode, AnalogScalingMode::Primary);
+    assert_eq!(difference.config.primary_factor, 1.0);
+    assert_eq!(difference.config.secondary_factor, 1.0);
+
+    let sum = record.derived_channel("IA + IB", "IA + IB").unwrap();
+    assert_eq!(sum.data, vec![2000.0, -1000.0]);
+}
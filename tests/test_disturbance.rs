@@ -0,0 +1,171 @@
+use chrono::Duration;
+
+use comtrade::analysis::{DisturbanceOptions, DisturbanceTrigger, PhaseGroup, Sequence, Threshold};
+use comtrade::Comtrade;
+
+mod common;
+
+use common::{stepped_sine_wave, synthetic_comtrade};
+
+const RATE: f64 = 2400.0;
+const NUM_SAMPLES: usize = 2400;
+const CYCLE: f64 = 1.0 / 60.0;
+
+/// One second record with a phase A current which steps from 100A to 1000A at
+/// 200ms and back at 300ms, and again from 700ms to 750ms.
+fn record() -> Comtrade {
+    let current = |angle: f64, faults: bool| {
+        let mut steps = vec![(0, 100.0, angle)];
+        if faults {
+            steps.extend([
+                (480, 1000.0, angle),
+                (720, 100.0, angle),
+                (1680, 1000.0, angle),
+                (1800, 100.0, angle),
+            ]);
+        }
+        stepped_sine_wave(RATE, NUM_SAMPLES, 60.0, &steps)
+    };
+    synthetic_comtrade(
+        RATE,
+        60.0,
+        vec![
+            ("IA", "A", "Line1", "A", current(0.0, true)),
+            ("IB", "B", "Line1", "A", current(-120.0, false)),
+            ("IC", "C", "Line1", "A", current(120.0, false)),
+        ],
+        vec![],
+    )
+}
+
+fn group() -> PhaseGroup {
+    PhaseGroup {
+        circuit_component: "Line1".to_string(),
+        units: "A".to_string(),
+        a: 0,
+        b: 1,
+        c: 2,
+    }
+}
+
+#[test]
+fn it_detects_rms_overcurrent() {
+    let trigger = DisturbanceTrigger::Rms {
+        channel: 0,
+        threshold: Threshold::Over(500.0),
+    };
+    let disturbances = record()
+        .detect_disturbances(
+            std::slice::from_ref(&trigger),
+            &DisturbanceOptions::default(),
+        )
+        .unwrap();
+
+    assert_eq!(disturbances.len(), 2);
+    assert_eq!(disturbances[0].trigger, trigger);
+    // The one cycle RMS lags the step by up to a cycle.
+    assert!(disturbances[0].start > 0.2 && disturbances[0].start < 0.2 + CYCLE);
+    assert!(disturbances[0].end > 0.3 && disturbances[0].end < 0.3 + CYCLE);
+    assert!(disturbances[1].start > 0.7 && disturbances[1].start < 0.7 + CYCLE);
+}
+
+#[test]
+fn it_detects_waveform_deviation_straight_away() {
+    let trigger = DisturbanceTrigger::WaveformDeviation {
+        channel: 0,
+        level: 50.0,
+    };
+    let disturbances = record()
+        .detect_disturbances(
+            std::slice::from_ref(&trigger),
+            &DisturbanceOptions::default(),
+        )
+        .unwrap();
+
+    // Deviation lasts a cycle after each step.
+    assert_eq!(disturbances.len(), 4);
+    assert!((disturbances[0].start - 0.2).abs() < 0.002);
+    assert!((disturbances[0].end - (0.2 + CYCLE)).abs() < 0.002);
+    assert!((disturbances[1].start - 0.3).abs() < 0.002);
+
+    // Steps closer together than the merge gap are joined up.
+    let options = DisturbanceOptions {
+        merge_gap: 0.1,
+        ..Default::default()
+    };
+    let disturbances = record().detect_disturbances(&[trigger], &options).unwrap();
+    assert_eq!(disturbances.len(), 2);
+    assert!((disturbances[0].end - (0.3 + CYCLE)).abs() < 0.002);
+    assert!((disturbances[1].start - 0.7).abs() < 0.002);
+}
+
+#[test]
+fn it_detects_rate_of_change_and_sequence_components() {
+    let triggers = [
+        DisturbanceTrigger::RateOfChange {
+            channel: 0,
+            rate: 10_000.0,
+        },
+        DisturbanceTrigger::Sequence {
+            group: group(),
+            sequence: Sequence::Negative,
+            threshold: Threshold::Over(100.0),
+        },
+    ];
+    let disturbances = record()
+        .detect_disturbances(&triggers, &DisturbanceOptions::default())
+        .unwrap();
+
+    let count = |trigger: &DisturbanceTrigger| {
+        disturbances
+            .iter()
+            .filter(|d| d.trigger == *trigger)
+            .count()
+    };
+    // The RMS changes for a cycle at each step.
+    assert_eq!(count(&triggers[0]), 4);
+    assert_eq!(count(&triggers[1]), 2);
+
+    // Sorted by start time across triggers.
+    assert!(disturbances.windows(2).all(|w| w[0].start <= w[1].start));
+    assert_eq!(triggers[1].channels(), vec![0, 1, 2]);
+}
+
+#[test]
+fn it_splits_records_into_events_and_estimates_trigger_time() {
+    let record = record();
+    let trigger = DisturbanceTrigger::WaveformDeviation {
+        channel: 0,
+        level: 50.0,
+    };
+    let disturbances = record
+        .detect_disturbances(&[trigger], &DisturbanceOptions::default())
+        .unwrap();
+
+    let estimated = record.estimate_trigger_time(&disturbances).unwrap();
+    assert!(
+        (estimated - record.start_time - Duration::milliseconds(200))
+            .num_microseconds()
+            .unwrap()
+            .abs()
+            < 2000
+    );
+    assert_eq!(record.estimate_trigger_time(&[]), None);
+
+    let events = record.split_events(&disturbances, 0.05, 0.05).unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].trigger_time, estimated);
+    assert!(
+        (events[0].start_time - record.start_time - Duration::milliseconds(150))
+            .num_microseconds()
+            .unwrap()
+            .abs()
+            < 2000
+    );
+    assert!(events[1].trigger_time > events[1].start_time);
+
+    // Pre and post trigger windows long enough to overlap combine the events.
+    let events = record.split_events(&disturbances, 0.3, 0.3).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].trigger_time, estimated);
+}
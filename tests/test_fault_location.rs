@@ -0,0 +1,164 @@
+use chrono::Duration;
+
+use comtrade::analysis::{
+    FaultLocationMethod, FaultLocationOptions, FaultLoop, LineParameters, Phasor,
+};
+use comtrade::Comtrade;
+
+mod common;
+
+use common::{stepped_sine_wave, synthetic_comtrade};
+
+const RATE: f64 = 2400.0;
+const NUM_SAMPLES: usize = 480;
+const FAULT_SAMPLE: usize = 240;
+
+fn line() -> LineParameters {
+    // 100km of line at 0.4Ω/km.
+    let positive_sequence = Phasor::from_polar(40.0, 80f64.to_radians());
+    LineParameters {
+        positive_sequence,
+        zero_sequence: positive_sequence * 3.0,
+        length: 100.0,
+    }
+}
+
+/// Record of a phase A to ground fault `distance` along the line through
+/// `fault_resistance`, with balanced load flowing beforehand.
+fn ground_fault_record(distance: f64, fault_resistance: f64) -> Comtrade {
+    let line = line();
+    let load = Phasor::from_polar(500.0, (-20f64).to_radians());
+    let fault_current = Phasor::from_polar(2000.0, (-75f64).to_radians());
+
+    // Load currents are balanced, so the residual current is the fault current.
+    let ia = load + fault_current;
+    let va = line.positive_sequence * (distance / line.length) * (ia + line.k0() * fault_current)
+        + fault_current * fault_resistance;
+
+    let wave = |pre: Phasor, fault: Phasor, scale: f64| {
+        stepped_sine_wave(
+            RATE,
+            NUM_SAMPLES,
+            60.0,
+            &[
+                (0, pre.magnitude() * scale, pre.angle_degrees()),
+                (
+                    FAULT_SAMPLE,
+                    fault.magnitude() * scale,
+                    fault.angle_degrees(),
+                ),
+            ],
+        )
+    };
+    let phase = |magnitude: f64, angle: f64| Phasor::from_polar(magnitude, angle.to_radians());
+
+    let mut record = synthetic_comtrade(
+        RATE,
+        60.0,
+        vec![
+            ("VA", "A", "Line1", "kV", wave(phase(63.5e3, 0.0), va, 1e-3)),
+            (
+                "VB",
+                "B",
+                "Line1",
+                "kV",
+                wave(phase(63.5e3, -120.0), phase(63.5e3, -120.0), 1e-3),
+            ),
+            (
+                "VC",
+                "C",
+                "Line1",
+                "kV",
+                wave(phase(63.5e3, 120.0), phase(63.5e3, 120.0), 1e-3),
+            ),
+            ("IA", "A", "Line1", "A", wave(load, ia, 1.0)),
+            (
+                "IB",
+                "B",
+                "Line1",
+                "A",
+                wave(
+                    load.rotate((-120f64).to_radians()),
+                    load.rotate((-120f64).to_radians()),
+                    1.0,
+                ),
+            ),
+            (
+                "IC",
+                "C",
+                "Line1",
+                "A",
+                wave(
+                    load.rotate(120f64.to_radians()),
+                    load.rotate(120f64.to_radians()),
+                    1.0,
+                ),
+            ),
+        ],
+        vec![],
+    );
+    record.trigger_time =
+        record.start_time + Duration::microseconds((FAULT_SAMPLE as f64 / RATE * 1e6) as i64);
+    record
+}
+
+#[test]
+fn it_locates_bolted_fault_with_reactance_method() {
+    let record = ground_fault_record(30.0, 0.0);
+    let group = &record.power_groups()[0];
+
+    let location = record
+        .fault_location(
+            group,
+            FaultLoop::AG,
+            &line(),
+            FaultLocationMethod::Reactance,
+            &FaultLocationOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
+    assert!((location.distance - 30.0).abs() < 1e-6);
+    assert!((location.per_unit - 0.3).abs() < 1e-8);
+}
+
+#[test]
+fn it_locates_resistive_fault_with_takagi_method() {
+    let record = ground_fault_record(30.0, 5.0);
+    let group = &record.power_groups()[0];
+    let locate = |method| {
+        record
+            .fault_location(
+                group,
+                FaultLoop::AG,
+                &line(),
+                method,
+                &FaultLocationOptions::default(),
+            )
+            .unwrap()
+            .unwrap()
+    };
+
+    let takagi = locate(FaultLocationMethod::Takagi);
+    assert!((takagi.distance - 30.0).abs() < 1e-6);
+
+    // The fault resistance throws out the simple reactance method.
+    let reactance = locate(FaultLocationMethod::Reactance);
+    assert!((reactance.distance - 30.0).abs() > 0.5);
+}
+
+#[test]
+fn it_needs_full_windows_around_trigger() {
+    let mut record = ground_fault_record(30.0, 0.0);
+    record.trigger_time = record.start_time;
+    let group = &record.power_groups()[0];
+    let location = record
+        .fault_location(
+            group,
+            FaultLoop::AG,
+            &line(),
+            FaultLocationMethod::Takagi,
+            &FaultLocationOptions::default(),
+        )
+        .unwrap();
+    assert_eq!(location, None);
+}
@@ -0,0 +1,333 @@
+use chrono::Duration;
+
+use comtrade::analysis::{FaultClassificationOptions, FaultLoop, FaultType, Phasor};
+use comtrade::status::StatusRoles;
+use comtrade::Comtrade;
+
+mod common;
+
+use common::{stepped_sine_wave, synthetic_comtrade};
+
+const RATE: f64 = 2400.0;
+const NUM_SAMPLES: usize = 600;
+const FAULT_SAMPLE: usize = 240;
+const TRIP_SAMPLE: usize = 300;
+const BREAKER_OPEN_SAMPLE: usize = 390;
+const CLEARING_SAMPLE: usize = 400;
+
+fn phasor(magnitude: f64, angle_degrees: f64) -> Phasor {
+    Phasor::from_polar(magnitude, angle_degrees.to_radians())
+}
+
+fn balanced(magnitude: f64, angle_degrees: f64) -> [Phasor; 3] {
+    [
+        phasor(magnitude, angle_degrees),
+        phasor(magnitude, angle_degrees - 120.0),
+        phasor(magnitude, angle_degrees + 120.0),
+    ]
+}
+
+/// Record with balanced load which changes to the given fault currents and
+/// voltages (in A and V) at 100ms, and is cleared by the breaker opening later.
+fn fault_record(fault_currents: [Phasor; 3], fault_voltages: [Phasor; 3]) -> Comtrade {
+    fault_record_cleared_at(fault_currents, fault_voltages, CLEARING_SAMPLE)
+}
+
+/// Record like [`fault_record`] with the current stopping at the given sample,
+/// which can be the end of the record. The TRIP and 52A status channels show the
+/// protection tripping and the breaker opening regardless.
+fn fault_record_cleared_at(
+    fault_currents: [Phasor; 3],
+    fault_voltages: [Phasor; 3],
+    clearing_sample: usize,
+) -> Comtrade {
+    let load = balanced(500.0, -20.0);
+    let voltages = balanced(63.5e3, 0.0);
+
+    let wave = |pre: Phasor, fault: Phasor, scale: f64| {
+        stepped_sine_wave(
+            RATE,
+            NUM_SAMPLES,
+            60.0,
+            &[
+                (0, pre.magnitude() * scale, pre.angle_degrees()),
+                (
+                    FAULT_SAMPLE,
+                    fault.magnitude() * scale,
+                    fault.angle_degrees(),
+                ),
+                (clearing_sample, 0.0, 0.0),
+            ],
+        )
+    };
+
+    let mut record = synthetic_comtrade(
+        RATE,
+        60.0,
+        vec![
+            (
+                "VA",
+                "A",
+                "Line1",
+                "kV",
+                wave(voltages[0], fault_voltages[0], 1e-3),
+            ),
+            (
+                "VB",
+                "B",
+                "Line1",
+                "kV",
+                wave(voltages[1], fault_voltages[1], 1e-3),
+            ),
+            (
+                "VC",
+                "C",
+                "Line1",
+                "kV",
+                wave(voltages[2], fault_voltages[2], 1e-3),
+            ),
+            (
+                "IA",
+                "A",
+                "Line1",
+                "A",
+                wave(load[0], fault_currents[0], 1.0),
+            ),
+            (
+                "IB",
+                "B",
+                "Line1",
+                "A",
+                wave(load[1], fault_currents[1], 1.0),
+            ),
+            (
+                "IC",
+                "C",
+                "Line1",
+                "A",
+                wave(load[2], fault_currents[2], 1.0),
+            ),
+        ],
+        vec![
+            (
+                "TRIP",
+                [vec![0; TRIP_SAMPLE], vec![1; NUM_SAMPLES - TRIP_SAMPLE]].concat(),
+            ),
+            (
+                "52A",
+                [
+                    vec![1; BREAKER_OPEN_SAMPLE],
+                    vec![0; NUM_SAMPLES - BREAKER_OPEN_SAMPLE],
+                ]
+                .concat(),
+            ),
+        ],
+    );
+    // Triggered a couple of milliseconds after inception.
+    record.trigger_time = record.start_time + Duration::microseconds(102_000);
+    record
+}
+
+fn classify(record: &Comtrade) -> comtrade::analysis::FaultClassification {
+    let group = &record.power_groups()[0];
+    record
+        .classify_fault(group, &FaultClassificationOptions::default())
+        .unwrap()
+}
+
+#[test]
+fn it_classifies_single_phase_to_ground_fault() {
+    let load = balanced(500.0, -20.0);
+    let voltages = balanced(63.5e3, 0.0);
+    let record = fault_record(
+        [load[0] + phasor(2000.0, -80.0), load[1], load[2]],
+        [phasor(30e3, 0.0), voltages[1], voltages[2]],
+    );
+
+    let classification = classify(&record);
+    assert_eq!(classification.fault_type, FaultType::AG);
+    assert_eq!(classification.fault_type.fault_loop(), Some(FaultLoop::AG));
+    assert!(classification.confidence > 0.5);
+
+    let inception = classification.inception.unwrap();
+    assert!((0.1..0.102).contains(&inception), "{}", inception);
+    let clearing = classification.clearing.unwrap();
+    let clearing_time = CLEARING_SAMPLE as f64 / RATE;
+    assert!(
+        (clearing_time..clearing_time + 0.002).contains(&clearing),
+        "{}",
+        clearing
+    );
+}
+
+#[test]
+fn it_does_not_mistake_decaying_dc_offset_for_clearing() {
+    let load = balanced(500.0, -20.0);
+    let voltages = balanced(63.5e3, 0.0);
+    let mut record = fault_record(
+        [load[0] + phasor(2000.0, -80.0), load[1], load[2]],
+        [phasor(30e3, 0.0), voltages[1], voltages[2]],
+    );
+
+    // Fully offset fault current on phase A, decaying with a 50ms time constant,
+    // which changes by more than the waveform threshold from one cycle to the
+    // next.
+    let fault_time = FAULT_SAMPLE as f64 / RATE;
+    let ia = &mut record.analog_channels[3].data;
+    for (n, value) in ia
+        .iter_mut()
+        .enumerate()
+        .take(CLEARING_SAMPLE)
+        .skip(FAULT_SAMPLE)
+    {
+        let t = n as f64 / RATE - fault_time;
+        *value += 2000.0 * std::f64::consts::SQRT_2 * (-t / 0.05).exp();
+    }
+
+    let classification = classify(&record);
+    assert_eq!(classification.fault_type, FaultType::AG);
+    let clearing = classification.clearing.unwrap();
+    let clearing_time = CLEARING_SAMPLE as f64 / RATE;
+    assert!(
+        (clearing_time..clearing_time + 0.002).contains(&clearing),
+        "{}",
+        clearing
+    );
+}
+
+#[test]
+fn it_classifies_phase_to_phase_faults() {
+    let load = balanced(500.0, -20.0);
+    let voltages = balanced(63.5e3, 0.0);
+    let change = phasor(2000.0, -170.0);
+    let collapsed = (voltages[1] + voltages[2]) / 2.0;
+
+    let bc = fault_record(
+        [load[0], load[1] + change, load[2] - change],
+        [voltages[0], collapsed, collapsed],
+    );
+    assert_eq!(classify(&bc).fault_type, FaultType::BC);
+
+    let bcg = fault_record(
+        [load[0], load[1] + change, load[2] + phasor(2000.0, 70.0)],
+        [voltages[0], phasor(10e3, -120.0), phasor(10e3, 120.0)],
+    );
+    assert_eq!(classify(&bcg).fault_type, FaultType::BCG);
+}
+
+#[test]
+fn it_classifies_three_phase_fault() {
+    let load = balanced(500.0, -20.0);
+    let change = balanced(3000.0, -80.0);
+    let record = fault_record(
+        [
+            load[0] + change[0],
+            load[1] + change[1],
+            load[2] + change[2],
+        ],
+        balanced(10e3, 0.0),
+    );
+
+    let classification = classify(&record);
+    assert_eq!(classification.fault_type, FaultType::ABC);
+    assert!(classification.confidence > 0.5);
+}
+
+#[test]
+fn it_finds_no_fault_in_steady_record() {
+    let record = fault_record(balanced(500.0, -20.0), balanced(63.5e3, 0.0));
+    let classification = classify(&record);
+    assert_eq!(classification.fault_type, FaultType::NoFault);
+    assert!(classification.confidence > 0.9);
+    assert_eq!(classification.inception, None);
+
+    // Without pre-fault data there's nothing to compare against.
+    let mut early_trigger = record.clone();
+    early_trigger.trigger_time = early_trigger.start_time;
+    let classification = classify(&early_trigger);
+    assert_eq!(classification.fault_type, FaultType::NoFault);
+    assert_eq!(classification.confidence, 0.0);
+}
+
+#[test]
+fn it_uses_negative_sequence_to_pick_phases_of_ungrounded_faults() {
+    // A weak BC fault, without much voltage drop, plus a balanced contribution
+    // from motor load. The phase selection is sensitive enough to pick up phase A
+    // from magnitudes alone.
+    let load = balanced(500.0, -20.0);
+    let voltages = balanced(63.5e3, 0.0);
+    let change = phasor(2000.0, -170.0);
+    let motors = balanced(400.0, -80.0);
+    let record = fault_record(
+        [
+            load[0] + motors[0],
+            load[1] + change + motors[1],
+            load[2] - change + motors[2],
+        ],
+        voltages,
+    );
+    let options = FaultClassificationOptions {
+        phase_selection: 0.15,
+        ..Default::default()
+    };
+    let group = &record.power_groups()[0];
+    let classification = record.classify_fault(group, &options).unwrap();
+    assert_eq!(classification.fault_type, FaultType::BC);
+
+    // A balanced fault has no negative sequence.
+    let change = balanced(3000.0, -80.0);
+    let record = fault_record(
+        [
+            load[0] + change[0],
+            load[1] + change[1],
+            load[2] + change[2],
+        ],
+        balanced(10e3, 0.0),
+    );
+    let classification = record.classify_fault(group, &options).unwrap();
+    assert_eq!(classification.fault_type, FaultType::ABC);
+}
+
+#[test]
+fn it_reports_trip_and_breaker_open_from_status_roles() {
+    let load = balanced(500.0, -20.0);
+    let voltages = balanced(63.5e3, 0.0);
+    let currents = [load[0] + phasor(2000.0, -80.0), load[1], load[2]];
+    let fault_voltages = [phasor(30e3, 0.0), voltages[1], voltages[2]];
+    let options = FaultClassificationOptions {
+        roles: Some(StatusRoles {
+            trip: vec!["TRIP".to_string()],
+            breaker_a: vec!["52A".to_string()],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let record = fault_record(currents, fault_voltages);
+    let group = &record.power_groups()[0];
+    let classification = record.classify_fault(group, &options).unwrap();
+    assert_eq!(classification.trip, Some(TRIP_SAMPLE as f64 / RATE));
+    assert_eq!(
+        classification.breaker_open,
+        Some(BREAKER_OPEN_SAMPLE as f64 / RATE)
+    );
+    let clearing = classification.clearing.unwrap();
+    let clearing_time = CLEARING_SAMPLE as f64 / RATE;
+    assert!(
+        (clearing_time..clearing_time + 0.002).contains(&clearing),
+        "{}",
+        clearing
+    );
+
+    // Without status roles, nothing is known about the trip.
+    assert_eq!(classify(&record).trip, None);
+
+    // If the current never stops within the record, the breaker opening stands
+    // in for the clearing time.
+    let record = fault_record_cleared_at(currents, fault_voltages, NUM_SAMPLES);
+    let classification = record.classify_fault(group, &options).unwrap();
+    assert_eq!(
+        classification.clearing,
+        Some(BREAKER_OPEN_SAMPLE as f64 / RATE)
+    );
+}
@@ -0,0 +1,223 @@
+use std::f64::consts::SQRT_2;
+
+use chrono::Duration;
+
+use comtrade::{Comtrade, ComtradeError, Filter, FilterOptions};
+
+mod common;
+
+use common::{sine_wave, synthetic_comtrade};
+
+const RATE: f64 = 2400.0;
+const NUM_SAMPLES: usize = 960;
+
+fn record_with(data: Vec<f64>) -> Comtrade {
+    synthetic_comtrade(RATE, 60.0, vec![("IA", "A", "Line1", "A", data)], vec![])
+}
+
+fn add(a: &[f64], b: &[f64]) -> Vec<f64> {
+    a.iter().zip(b.iter()).map(|(a, b)| a + b).collect()
+}
+
+/// Largest difference between the channel and `expected`, ignoring the first and
+/// last 100ms where the filters are settling.
+fn max_error(record: &Comtrade, channel: usize, expected: &[f64]) -> f64 {
+    let settle = (0.1 * RATE) as usize;
+    record.analog_channels[channel].data[settle..NUM_SAMPLES - settle]
+        .iter()
+        .zip(expected[settle..].iter())
+        .map(|(v, e)| (v - e).abs())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn it_low_pass_filters_with_zero_phase() {
+    let fundamental = sine_wave(RATE, NUM_SAMPLES, 60.0, 100.0, 30.0);
+    let noise = sine_wave(RATE, NUM_SAMPLES, 900.0, 10.0, 0.0);
+    let mut record = record_with(add(&fundamental, &noise));
+
+    for filter in [
+        Filter::LowPass {
+            cutoff_hz: 300.0,
+            order: 4,
+        },
+        Filter::FirLowPass {
+            cutoff_hz: 300.0,
+            taps: 63,
+        },
+    ] {
+        let options = FilterOptions {
+            filter,
+            zero_phase: true,
+        };
+        let filtered = record.filtered_channel(0, &options, "IA LPF").unwrap();
+        assert_eq!(filtered.config.name, "IA LPF");
+        assert_eq!(filtered.config.index.get(), 2);
+        record.analog_channels.push(filtered);
+        assert!(max_error(&record, 1, &fundamental) < 0.5);
+        record.analog_channels.pop();
+    }
+}
+
+#[test]
+fn it_high_pass_and_band_pass_filters() {
+    let fundamental = sine_wave(RATE, NUM_SAMPLES, 60.0, 100.0, 0.0);
+    let offset = vec![50.0; NUM_SAMPLES];
+    let harmonic = sine_wave(RATE, NUM_SAMPLES, 300.0, 10.0, 0.0);
+
+    let mut record = record_with(add(&add(&fundamental, &offset), &harmonic));
+    let options = FilterOptions {
+        filter: Filter::HighPass {
+            cutoff_hz: 10.0,
+            order: 2,
+        },
+        zero_phase: true,
+    };
+    record.filter_channel(0, &options).unwrap();
+    assert!(max_error(&record, 0, &add(&fundamental, &harmonic)) < 1.0);
+
+    let mut record = record_with(add(&add(&fundamental, &offset), &harmonic));
+    let options = FilterOptions {
+        filter: Filter::FirBandPass {
+            low_hz: 200.0,
+            high_hz: 400.0,
+            taps: 101,
+        },
+        zero_phase: true,
+    };
+    record.filter_channel(0, &options).unwrap();
+    assert!(max_error(&record, 0, &harmonic) < 0.5);
+}
+
+#[test]
+fn it_notches_out_line_frequency_and_harmonics() {
+    let fundamental = sine_wave(RATE, NUM_SAMPLES, 60.0, 100.0, 0.0);
+    let third = sine_wave(RATE, NUM_SAMPLES, 180.0, 20.0, 0.0);
+    let interharmonic = sine_wave(RATE, NUM_SAMPLES, 610.0, 5.0, 0.0);
+    let mut record = record_with(add(&add(&fundamental, &third), &interharmonic));
+
+    let options = FilterOptions {
+        filter: Filter::LineNotch {
+            max_order: 3,
+            quality: 3.0,
+        },
+        zero_phase: true,
+    };
+    record.filter_channel(0, &options).unwrap();
+    assert!(max_error(&record, 0, &interharmonic) < 0.5);
+}
+
+#[test]
+fn it_removes_decaying_dc_offset_with_mimic_filter() {
+    let time_constant = 0.05;
+    let fundamental = sine_wave(RATE, NUM_SAMPLES, 60.0, 100.0, 0.0);
+    // Fully offset fault current: the DC offset cancels the initial AC value.
+    let offset: Vec<f64> = (0..NUM_SAMPLES)
+        .map(|n| -100.0 * SQRT_2 * (-(n as f64) / RATE / time_constant).exp())
+        .collect();
+    let mut record = record_with(add(&fundamental, &offset));
+
+    let options = FilterOptions {
+        filter: Filter::Mimic { time_constant },
+        zero_phase: false,
+    };
+    record.filter_channel(0, &options).unwrap();
+
+    // The mimic filter shifts the phase, so compare magnitudes over a cycle after
+    // the first sample.
+    let cycle = (RATE / 60.0) as usize;
+    let data = &record.analog_channels[0].data;
+    let peak = data[1..1 + cycle]
+        .iter()
+        .fold(0.0f64, |m, v| m.max(v.abs()));
+    let rms = (data[1..1 + cycle].iter().map(|v| v * v).sum::<f64>() / cycle as f64).sqrt();
+    assert!((peak - 100.0 * SQRT_2).abs() < 3.0);
+    assert!((rms - 100.0).abs() < 1.0);
+    let mean = data[1..1 + cycle].iter().sum::<f64>() / cycle as f64;
+    assert!(mean.abs() < 1.0, "{}", mean);
+}
+
+#[test]
+fn it_rejects_unsuitable_filters_and_records() {
+    let mut record = record_with(sine_wave(RATE, 100, 60.0, 1.0, 0.0));
+    let options = FilterOptions {
+        filter: Filter::LowPass {
+            cutoff_hz: 1500.0,
+            order: 2,
+        },
+        zero_phase: false,
+    };
+    assert_eq!(
+        record.filter_channel(0, &options),
+        Err(ComtradeError::InvalidFilterFrequency(1500.0))
+    );
+
+    // Band edges the wrong way round.
+    for filter in [
+        Filter::BandPass {
+            low_hz: 300.0,
+            high_hz: 100.0,
+            order: 2,
+        },
+        Filter::FirBandPass {
+            low_hz: 300.0,
+            high_hz: 100.0,
+            taps: 31,
+        },
+    ] {
+        let options = FilterOptions {
+            filter,
+            zero_phase: false,
+        };
+        assert_eq!(
+            record.filter_channel(0, &options),
+            Err(ComtradeError::InvalidFilterFrequency(300.0))
+        );
+    }
+
+    let options = FilterOptions {
+        filter: Filter::LineNotch {
+            max_order: 3,
+            quality: 0.0,
+        },
+        zero_phase: false,
+    };
+    assert_eq!(
+        record.filter_channel(0, &options),
+        Err(ComtradeError::InvalidFilterParameter {
+            name: "quality",
+            value: 0.0
+        })
+    );
+    let options = FilterOptions {
+        filter: Filter::Mimic {
+            time_constant: -0.05,
+        },
+        zero_phase: false,
+    };
+    assert_eq!(
+        record.filter_channel(0, &options),
+        Err(ComtradeError::InvalidFilterParameter {
+            name: "time constant",
+            value: -0.05
+        })
+    );
+
+    let mut other = record_with(sine_wave(RATE / 2.0, 100, 60.0, 1.0, 0.0));
+    other.sampling_rates[0].rate_hz = RATE / 2.0;
+    other.timestamps = (0..100).map(|n| n as f64 * 2.0 / RATE).collect();
+    other.start_time = record.start_time + Duration::nanoseconds((101.0 / RATE * 1e9) as i64);
+    record.append(&other).unwrap();
+
+    let options = FilterOptions {
+        filter: Filter::LowPass {
+            cutoff_hz: 300.0,
+            order: 2,
+        },
+        zero_phase: false,
+    };
+    assert_eq!(
+        record.filter_channel(0, &options),
+        Err(ComtradeError::NonUniformSampling)
+    );
+}
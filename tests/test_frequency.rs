@@ -0,0 +1,74 @@
+use comtrade::analysis::{FrequencyMethod, Phasor, PhasorOptions, RmsWindow, TimeSeries};
+
+mod common;
+
+use common::{sine_wave, synthetic_comtrade};
+
+#[test]
+fn it_tracks_off_nominal_frequency() {
+    let rate = 4800.0;
+    let record = synthetic_comtrade(
+        rate,
+        50.0,
+        vec![(
+            "VA",
+            "A",
+            "Bus1",
+            "kV",
+            sine_wave(rate, 2400, 50.5, 63.5, 10.0),
+        )],
+        vec![],
+    );
+
+    for method in [FrequencyMethod::ZeroCrossing, FrequencyMethod::PhasorAngle] {
+        let frequency = record.frequency(0, method).unwrap();
+        assert!(!frequency.is_empty());
+        for (t, f) in frequency.iter() {
+            assert!((f - 50.5).abs() < 0.05, "{:?} at {}: {} Hz", method, t, f);
+        }
+    }
+}
+
+#[test]
+fn it_adapts_phasor_and_rms_windows_to_tracked_frequency() {
+    let rate = 4800.0;
+    let record = synthetic_comtrade(
+        rate,
+        50.0,
+        vec![(
+            "VA",
+            "A",
+            "Bus1",
+            "kV",
+            sine_wave(rate, 2400, 47.0, 63.5, 0.0),
+        )],
+        vec![],
+    );
+    let frequency = record.frequency(0, FrequencyMethod::ZeroCrossing).unwrap();
+    let start = frequency.timestamps[0];
+
+    let options = PhasorOptions::default();
+    let nominal = record.phasors(0, &options).unwrap();
+    let tracked = record.phasors_tracking(0, &options, &frequency).unwrap();
+    let worst_error = |series: &TimeSeries<Phasor>| {
+        series
+            .iter()
+            .filter(|(t, _)| *t > start + 0.03)
+            .map(|(_, p)| (p.magnitude() - 63.5).abs())
+            .fold(0.0, f64::max)
+    };
+    assert!(worst_error(&tracked) < 0.1);
+    assert!(worst_error(&nominal) > 1.0);
+
+    let rms = record.rms_tracking(0, 1.0, &frequency).unwrap();
+    let nominal_rms = record.rms(0, RmsWindow::Cycles(1.0)).unwrap();
+    let worst_rms_error = |series: &TimeSeries<f64>| {
+        series
+            .iter()
+            .filter(|(t, _)| *t > start + 0.03)
+            .map(|(_, v)| (v - 63.5).abs())
+            .fold(0.0, f64::max)
+    };
+    assert!(worst_rms_error(&rms) < 0.1);
+    assert!(worst_rms_error(&nominal_rms) > 0.5);
+}
@@ -0,0 +1,46 @@
+use comtrade::analysis::HarmonicOptions;
+
+mod common;
+
+use common::{sine_wave, synthetic_comtrade};
+
+#[test]
+fn it_tracks_second_harmonic_over_time() {
+    let rate = 1920.0;
+    let fundamental = sine_wave(rate, 385, 50.0, 100.0, 0.0);
+    let second = sine_wave(rate, 385, 100.0, 30.0, -90.0);
+
+    // Second harmonic is only present for the first five cycles, and passes through
+    // zero at the end of the fifth cycle.
+    let data: Vec<f64> = fundamental
+        .iter()
+        .zip(second.iter())
+        .enumerate()
+        .map(|(i, (f, s))| if i < 192 { f + s } else { *f })
+        .collect();
+    let record = synthetic_comtrade(rate, 50.0, vec![("IA", "A", "T1", "A", data)], vec![]);
+
+    let options = HarmonicOptions {
+        max_order: 5,
+        ..HarmonicOptions::default()
+    };
+    let spectra = record.harmonics(0, &options).unwrap();
+    assert_eq!(spectra.len(), 10);
+
+    let ratios: Vec<f64> = spectra.values.iter().map(|s| s.harmonic_ratio(2)).collect();
+    // 38.4 samples per cycle, so the window edges fall between samples.
+    for ratio in &ratios[..5] {
+        assert!((ratio - 0.3).abs() < 1e-3);
+    }
+    for ratio in &ratios[5..] {
+        assert!(*ratio < 1e-3);
+    }
+
+    let spectrum = record
+        .harmonic_spectrum(0, 0.0, &HarmonicOptions::default())
+        .unwrap()
+        .unwrap();
+    // Harmonics up to the 19th are below the Nyquist frequency.
+    assert_eq!(spectrum.phasors.len(), 20);
+    assert!((spectrum.thd() - 0.3).abs() < 1e-2);
+}
@@ -0,0 +1,92 @@
+use comtrade::analysis::{FaultLoop, ImpedanceOptions, Phasor, ZoneCharacteristic};
+
+mod common;
+
+use common::{sine_wave, synthetic_comtrade};
+
+#[test]
+fn it_calculates_apparent_impedance_of_ground_fault() {
+    let rate = 2400.0;
+    let n = 240;
+    let line_angle = 80.0;
+
+    // Phase A fault 10Ω along a line with k0 = 0.5, fed from one end only so the
+    // residual current is the phase A current: Va = Z1 (1 + k0) Ia.
+    let record = synthetic_comtrade(
+        rate,
+        60.0,
+        vec![
+            (
+                "VA",
+                "A",
+                "Line1",
+                "kV",
+                sine_wave(rate, n, 60.0, 15.0, 0.0),
+            ),
+            (
+                "VB",
+                "B",
+                "Line1",
+                "kV",
+                sine_wave(rate, n, 60.0, 63.5, -120.0),
+            ),
+            (
+                "VC",
+                "C",
+                "Line1",
+                "kV",
+                sine_wave(rate, n, 60.0, 63.5, 120.0),
+            ),
+            (
+                "IA",
+                "A",
+                "Line1",
+                "kA",
+                sine_wave(rate, n, 60.0, 1.0, -line_angle),
+            ),
+            ("IB", "B", "Line1", "kA", vec![0.0; n]),
+            ("IC", "C", "Line1", "kA", vec![0.0; n]),
+        ],
+        vec![],
+    );
+
+    let groups = record.power_groups();
+    assert_eq!(groups.len(), 1);
+    let options = ImpedanceOptions {
+        k0: Phasor::new(0.5, 0.0),
+        ..ImpedanceOptions::default()
+    };
+
+    let loops = record.impedance_loops(&groups[0], &options).unwrap();
+    assert_eq!(loops.len(), 6);
+
+    let ag = &loops[0];
+    assert_eq!(ag.fault_loop, FaultLoop::AG);
+    assert!(!ag.impedance.is_empty());
+    for z in ag.impedance.values.iter() {
+        assert!((z.magnitude() - 10.0).abs() < 1e-6);
+        assert!((z.angle_degrees() - line_angle).abs() < 1e-6);
+    }
+    let reactance = ag.reactance();
+    assert!((reactance.values[0] - 10.0 * line_angle.to_radians().sin()).abs() < 1e-6);
+
+    // No current in phases B and C, so the BC loop has nothing to measure.
+    let bc = record
+        .apparent_impedance(&groups[0], FaultLoop::BC, &options)
+        .unwrap();
+    assert!(bc.impedance.is_empty());
+
+    let zone_1 = ZoneCharacteristic::Mho {
+        reach: 12.0,
+        angle: line_angle,
+        reverse_reach: 0.0,
+    };
+    let zone_short = ZoneCharacteristic::Mho {
+        reach: 8.0,
+        angle: line_angle,
+        reverse_reach: 0.0,
+    };
+    assert_eq!(ag.zone_entry(&zone_1), Some(ag.impedance.timestamps[0]));
+    assert_eq!(ag.zone_entry(&zone_short), None);
+    assert!(ag.in_zone(&zone_1).values.iter().all(|inside| *inside));
+}
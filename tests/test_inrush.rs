@@ -0,0 +1,115 @@
+use std::f64::consts::PI;
+
+use comtrade::analysis::{InrushOptions, SaturationOptions};
+use comtrade::Comtrade;
+
+mod common;
+
+use common::{sine_wave, synthetic_comtrade};
+
+const RATE: f64 = 2400.0;
+const NUM_SAMPLES: usize = 1200;
+const CYCLE: f64 = 1.0 / 60.0;
+
+fn record(data: Vec<f64>) -> Comtrade {
+    synthetic_comtrade(RATE, 60.0, vec![("IA", "A", "Xfmr1", "A", data)], vec![])
+}
+
+/// Half second of 100A load current, replaced with `during` between 100ms and
+/// 300ms.
+fn with_event(during: impl Fn(f64) -> f64) -> Vec<f64> {
+    sine_wave(RATE, NUM_SAMPLES, 60.0, 100.0, 0.0)
+        .into_iter()
+        .enumerate()
+        .map(|(n, load)| {
+            let t = n as f64 / RATE;
+            if (0.1..0.3).contains(&t) {
+                during(t)
+            } else {
+                load
+            }
+        })
+        .collect()
+}
+
+/// Unipolar inrush pulses, conducting for 120° of each cycle.
+fn inrush(t: f64) -> f64 {
+    let peak = 2000.0;
+    (peak * ((2.0 * PI * 60.0 * t).cos() - 0.5)).max(0.0)
+}
+
+/// 2000A fault current through a CT which saturates 60° into each half cycle and
+/// gives nothing until the current reverses.
+fn saturated(t: f64) -> f64 {
+    let angle = (2.0 * PI * 60.0 * t) % PI;
+    let current = 2000.0 * (2.0 * PI * 60.0 * t).sin();
+    if angle < PI / 3.0 {
+        current
+    } else {
+        0.0
+    }
+}
+
+#[test]
+fn it_detects_inrush() {
+    let record = record(with_event(inrush));
+    let options = InrushOptions {
+        pickup: 10.0,
+        ..Default::default()
+    };
+    let intervals = record.detect_inrush(0, &options).unwrap();
+
+    assert_eq!(intervals.len(), 1);
+    assert!((intervals[0].start - 0.1).abs() <= CYCLE);
+    assert!((intervals[0].end - 0.3).abs() <= CYCLE);
+    // Windows straddling the edges only see part of the inrush.
+    assert!(intervals[0].confidence > 0.8);
+}
+
+#[test]
+fn it_detects_ct_saturation() {
+    let record = record(with_event(saturated));
+    let options = SaturationOptions {
+        pickup: 10.0,
+        ..Default::default()
+    };
+    let intervals = record.detect_ct_saturation(0, &options).unwrap();
+
+    assert_eq!(intervals.len(), 1);
+    assert!((intervals[0].start - 0.1).abs() <= CYCLE);
+    assert!((intervals[0].end - 0.3).abs() <= CYCLE);
+    assert!(intervals[0].confidence > 0.8);
+
+    // Symmetrical saturation has gaps but no 2nd harmonic, other than in windows
+    // straddling its edges, so looks less like inrush.
+    let inrush = record
+        .detect_inrush(
+            0,
+            &InrushOptions {
+                pickup: 10.0,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert!(inrush.iter().all(|i| i.confidence < 0.6));
+}
+
+#[test]
+fn it_finds_nothing_in_undistorted_current() {
+    let record = record(sine_wave(RATE, NUM_SAMPLES, 60.0, 100.0, 30.0));
+    assert!(record
+        .detect_inrush(0, &InrushOptions::default())
+        .unwrap()
+        .is_empty());
+    assert!(record
+        .detect_ct_saturation(0, &SaturationOptions::default())
+        .unwrap()
+        .is_empty());
+
+    // Nor in a channel with nothing on it.
+    let record = self::record(vec![0.0; NUM_SAMPLES]);
+    assert!(record
+        .detect_ct_saturation(0, &SaturationOptions::default())
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,114 @@
+use chrono::{Duration, FixedOffset};
+use float_cmp::approx_eq;
+
+use comtrade::{Comtrade, ComtradeError, TimeQuality};
+
+mod common;
+
+use common::load_comtrade;
+
+fn second_device(record: &Comtrade) -> Comtrade {
+    let mut other = record.clone();
+    other.station_name = "OTHERSTATION".to_string();
+    other.recording_device_id = "DFR1".to_string();
+    other
+}
+
+#[test]
+fn it_merges_records_onto_a_common_timeline() {
+    let a = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let mut b = second_device(&a);
+
+    // Device B started recording 5ms (6 samples) after device A, but its local
+    // clock is an hour ahead so it reports a start time an hour later.
+    b.start_time = a.start_time + Duration::hours(1) + Duration::milliseconds(5);
+    b.trigger_time = a.trigger_time + Duration::hours(1);
+    b.time_offset = Some(FixedOffset::east(
+        a.time_offset.unwrap().local_minus_utc() + 3600,
+    ));
+
+    let merged = Comtrade::merge(&[a.clone(), b.clone()], 1200.0).expect("unable to merge");
+    let record = merged.record;
+
+    assert_eq!(record.analog_channels.len(), 8);
+    assert_eq!(record.status_channels.len(), 8);
+    assert_eq!(
+        record.analog_channels[0].config.name,
+        "SMARTSTATION/IED123/IA"
+    );
+    assert_eq!(
+        record.analog_channels[4].config.name,
+        "OTHERSTATION/DFR1/IA"
+    );
+    assert_eq!(record.analog_channels[4].config.index.get(), 5);
+
+    assert_eq!(record.start_time, a.start_time + Duration::milliseconds(5));
+    assert_eq!(record.trigger_time, a.trigger_time);
+    assert_eq!(record.timestamps.len(), 34);
+    assert_eq!(record.sampling_rates[0].end_sample_number, 34);
+
+    for i in 0..34 {
+        assert!(approx_eq!(
+            f64,
+            record.analog_channels[0].data[i],
+            a.analog_channels[0].data[i + 6],
+            epsilon = 1e-6
+        ));
+        assert!(approx_eq!(
+            f64,
+            record.analog_channels[4].data[i],
+            b.analog_channels[0].data[i],
+            epsilon = 1e-6
+        ));
+    }
+}
+
+#[test]
+fn it_reports_alignment_uncertainty_from_time_quality() {
+    let a = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let mut b = second_device(&a);
+    let mut c = second_device(&a);
+
+    b.time_quality = Some(TimeQuality::ClockUnlocked(-3));
+    c.time_quality = Some(TimeQuality::ClockLocked);
+
+    let merged = Comtrade::merge(&[a.clone(), b.clone(), c], 2400.0).expect("unable to merge");
+    assert_eq!(merged.alignment_uncertainty, Some(10.0 + 0.001));
+    assert_eq!(
+        merged.record.time_quality,
+        Some(TimeQuality::ClockUnlocked(1))
+    );
+    assert_eq!(merged.record.timestamps.len(), 79);
+
+    b.time_quality = None;
+    let merged = Comtrade::merge(&[a, b], 1200.0).expect("unable to merge");
+    assert_eq!(merged.alignment_uncertainty, None);
+}
+
+#[test]
+fn it_rejects_records_which_cant_be_merged() {
+    let a = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let mut b = second_device(&a);
+    b.start_time = a.start_time + Duration::seconds(1);
+
+    assert_eq!(Comtrade::merge(&[], 1200.0), Err(ComtradeError::NoRecords));
+    assert_eq!(
+        Comtrade::merge(&[a.clone(), b.clone()], 1200.0),
+        Err(ComtradeError::NoOverlap)
+    );
+    assert_eq!(
+        Comtrade::merge(std::slice::from_ref(&a), 0.0),
+        Err(ComtradeError::InvalidSamplingRate(0.0))
+    );
+    assert!(matches!(
+        Comtrade::merge(std::slice::from_ref(&a), 600.0),
+        Err(ComtradeError::SamplingRateTooLow(rate, input)) if rate == 600.0 && approx_eq!(f64, input, 1200.0, epsilon = 1e-6)
+    ));
+
+    b.start_time = a.start_time;
+    b.line_frequency = 50.0;
+    assert_eq!(
+        Comtrade::merge(&[a, b], 1200.0),
+        Err(ComtradeError::MismatchedLineFrequency(60.0, 50.0))
+    );
+}
@@ -0,0 +1,136 @@
+use comtrade::analysis::PhasorOptions;
+use comtrade::{AnalogScalingMode, ComtradeError};
+
+mod common;
+
+use common::{sine_wave, synthetic_comtrade};
+
+#[test]
+fn it_calculates_per_phase_and_three_phase_power() {
+    let rate = 2400.0;
+    let n = 480;
+    // 63.5 kV phase-to-neutral, 0.4 kA lagging by 30°.
+    let mut record = synthetic_comtrade(
+        rate,
+        60.0,
+        vec![
+            (
+                "VA",
+                "A",
+                "Line1",
+                "kV",
+                sine_wave(rate, n, 60.0, 63.5, 0.0),
+            ),
+            (
+                "VB",
+                "B",
+                "Line1",
+                "kV",
+                sine_wave(rate, n, 60.0, 63.5, -120.0),
+            ),
+            (
+                "VC",
+                "C",
+                "Line1",
+                "kV",
+                sine_wave(rate, n, 60.0, 63.5, 120.0),
+            ),
+            (
+                "IA",
+                "A",
+                "Line1",
+                "A",
+                sine_wave(rate, n, 60.0, 0.4, -30.0),
+            ),
+            (
+                "IB",
+                "B",
+                "Line1",
+                "A",
+                sine_wave(rate, n, 60.0, 0.4, -150.0),
+            ),
+            ("IC", "C", "Line1", "A", sine_wave(rate, n, 60.0, 0.4, 90.0)),
+        ],
+        vec![],
+    );
+
+    // Currents are recorded in secondary amps through a 1000:1 CT.
+    for channel in record.analog_channels[3..].iter_mut() {
+        channel.config.scaling_mode = AnalogScalingMode::Secondary;
+        channel.config.primary_factor = 1000.0;
+        channel.config.secondary_factor = 1.0;
+    }
+
+    let options = PhasorOptions {
+        scaling: Some(AnalogScalingMode::Primary),
+        ..PhasorOptions::default()
+    };
+
+    let apparent = 63.5e3 * 400.0;
+    let phase_a = record.power(0, 3, &options).unwrap();
+    for power in phase_a.values.iter() {
+        assert!((power.apparent - apparent).abs() < apparent * 1e-6);
+        assert!((power.power_factor - 30f64.to_radians().cos()).abs() < 1e-6);
+        assert!(power.reactive > 0.0);
+    }
+
+    let groups = record.power_groups();
+    assert_eq!(groups.len(), 1);
+    let total = record.three_phase_power(&groups[0], &options).unwrap();
+    for power in total.values.iter() {
+        assert!((power.apparent - 3.0 * apparent).abs() < apparent * 1e-5);
+    }
+
+    // Instantaneous three-phase power of a balanced system is constant.
+    let p: Vec<f64> = (0..3)
+        .map(|phase| {
+            record
+                .instantaneous_power(phase, phase + 3, &options)
+                .unwrap()
+        })
+        .fold(vec![0.0; n], |total, series| {
+            total
+                .iter()
+                .zip(series.values.iter())
+                .map(|(a, b)| a + b)
+                .collect()
+        });
+    let active = 3.0 * apparent * 30f64.to_radians().cos();
+    for value in p.iter() {
+        assert!((value - active).abs() < active * 1e-6);
+    }
+
+    let energy = total.energy();
+    let duration = total.timestamps.last().unwrap() - total.timestamps[0];
+    assert!((energy.active - active * duration / 3600.0).abs() < active * 1e-6);
+}
+
+#[test]
+fn it_rejects_unrecognised_units() {
+    let rate = 1200.0;
+    let record = synthetic_comtrade(
+        rate,
+        60.0,
+        vec![
+            (
+                "VA",
+                "A",
+                "Line1",
+                "pu",
+                sine_wave(rate, 100, 60.0, 1.0, 0.0),
+            ),
+            (
+                "IA",
+                "A",
+                "Line1",
+                "A",
+                sine_wave(rate, 100, 60.0, 1.0, 0.0),
+            ),
+        ],
+        vec![],
+    );
+    assert_eq!(
+        record.power(0, 1, &PhasorOptions::default()),
+        Err(ComtradeError::UnrecognisedUnits("pu".to_string()))
+    );
+}
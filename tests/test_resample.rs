@@ -0,0 +1,148 @@
+use std::ops::Range;
+
+use chrono::Duration;
+
+use comtrade::{Comtrade, ComtradeError, ResampleOptions, SamplingRate};
+
+mod common;
+
+use common::{sine_wave, synthetic_comtrade};
+
+/// Largest difference from a 60Hz sine wave with the given RMS magnitude over
+/// the samples in `times`.
+fn max_error(record: &Comtrade, magnitude: f64, times: Range<f64>) -> f64 {
+    record
+        .timestamps
+        .iter()
+        .zip(record.analog_channels[0].data.iter())
+        .filter(|(t, _)| times.contains(*t))
+        .map(|(t, v)| {
+            let expected = magnitude
+                * std::f64::consts::SQRT_2
+                * (2.0 * std::f64::consts::PI * 60.0 * t).cos();
+            (v - expected).abs()
+        })
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn it_upsamples_to_a_single_rate() {
+    let record = synthetic_comtrade(
+        1200.0,
+        60.0,
+        vec![(
+            "IA",
+            "A",
+            "Line1",
+            "A",
+            sine_wave(1200.0, 240, 60.0, 100.0, 0.0),
+        )],
+        vec![("TRIP", [vec![0; 120], vec![1; 120]].concat())],
+    );
+
+    let resampled = record
+        .resample(4800.0, &ResampleOptions::default())
+        .unwrap();
+    assert_eq!(resampled.timestamps.len(), 957);
+    assert_eq!(
+        resampled.sampling_rates,
+        vec![SamplingRate {
+            rate_hz: 4800.0,
+            end_sample_number: 957
+        }]
+    );
+    assert_eq!(resampled.sample_numbers[956], 957);
+    assert!(max_error(&resampled, 100.0, 0.01..0.19) < 0.5);
+
+    // Status holds its value until the next input sample.
+    let trip = &resampled.status_channels[0].data;
+    assert_eq!(trip.iter().position(|s| *s == 1), Some(480));
+}
+
+#[test]
+fn it_filters_out_frequencies_above_the_new_nyquist_frequency() {
+    let fundamental = sine_wave(4800.0, 960, 60.0, 100.0, 0.0);
+    let high = sine_wave(4800.0, 960, 60.0 * 17.0, 20.0, 0.0);
+    let data = fundamental
+        .iter()
+        .zip(high.iter())
+        .map(|(a, b)| a + b)
+        .collect();
+    let record = synthetic_comtrade(4800.0, 60.0, vec![("IA", "A", "Line1", "A", data)], vec![]);
+
+    let resampled = record.resample(960.0, &ResampleOptions::default()).unwrap();
+    assert_eq!(resampled.timestamps.len(), 192);
+    assert!(max_error(&resampled, 100.0, 0.02..0.18) < 1.0);
+}
+
+#[test]
+fn it_resamples_records_with_several_sampling_rates() {
+    let first = synthetic_comtrade(
+        1200.0,
+        60.0,
+        vec![(
+            "IA",
+            "A",
+            "Line1",
+            "A",
+            sine_wave(1200.0, 120, 60.0, 100.0, 0.0),
+        )],
+        vec![],
+    );
+    // Carries straight on one sample interval at the new rate after the last sample.
+    let offset = 119.0 / 1200.0 + 1.0 / 2400.0;
+    let angle = 360.0 * 60.0 * offset;
+    let mut second = synthetic_comtrade(
+        2400.0,
+        60.0,
+        vec![(
+            "IA",
+            "A",
+            "Line1",
+            "A",
+            sine_wave(2400.0, 240, 60.0, 100.0, angle),
+        )],
+        vec![],
+    );
+    second.start_time = first.start_time + Duration::nanoseconds((offset * 1e9).round() as i64);
+    let record = Comtrade::concat(&[first, second]).unwrap();
+    assert_eq!(record.sampling_rates.len(), 2);
+
+    let resampled = record
+        .resample(1920.0, &ResampleOptions::default())
+        .unwrap();
+    assert_eq!(resampled.sampling_rates.len(), 1);
+    assert!(max_error(&resampled, 100.0, 0.01..0.19) < 2.0);
+
+    // Away from the change in rate, it's as accurate as for a single rate.
+    assert!(max_error(&resampled, 100.0, 0.01..0.09) < 0.5);
+    assert!(max_error(&resampled, 100.0, 0.11..0.19) < 0.5);
+}
+
+#[test]
+fn it_rejects_invalid_rates() {
+    let record = synthetic_comtrade(
+        1200.0,
+        60.0,
+        vec![("IA", "A", "Line1", "A", vec![0.0; 10])],
+        vec![],
+    );
+    assert_eq!(
+        record.resample(0.0, &ResampleOptions::default()),
+        Err(ComtradeError::InvalidSamplingRate(0.0))
+    );
+    for (cutoff, zero_crossings, name, value) in [
+        (0.0, 8, "cutoff", 0.0),
+        (1.5, 8, "cutoff", 1.5),
+        (0.9, 0, "zero_crossings", 0.0),
+    ] {
+        let options = ResampleOptions {
+            cutoff,
+            zero_crossings,
+        };
+        assert_eq!(
+            record.resample(2400.0, &options),
+            Err(ComtradeError::InvalidFilterParameter { name, value })
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use comtrade::analysis::RmsWindow;
+
+mod common;
+
+use common::load_comtrade;
+
+#[test]
+fn it_calculates_sliding_and_stepped_rms() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+
+    // 20 samples per cycle at 1200 Hz and 60 Hz.
+    let sliding = record.rms(0, RmsWindow::Cycles(1.0)).unwrap();
+    assert_eq!(sliding.len(), 20);
+    assert!((sliding.timestamps[0] - 20.0 / 1200.0).abs() < 1e-9);
+
+    let cycles = record.cycle_rms(0).unwrap();
+    assert_eq!(cycles.len(), 1);
+    assert!((cycles.values[0] - sliding.values[0]).abs() < 1e-9);
+
+    let half_cycles = record.half_cycle_rms(0).unwrap();
+    assert_eq!(half_cycles.len(), 2);
+    assert!((half_cycles.values[1] - sliding.values[10]).abs() < 1e-9);
+
+    // The current is roughly a 26A peak sine wave.
+    for rms in sliding.values.iter() {
+        assert!(*rms > 15.0 && *rms < 21.0, "unexpected RMS {}", rms);
+    }
+
+    let short = record.rms(0, RmsWindow::Duration(0.005)).unwrap();
+    assert_eq!(short.len(), 34);
+}
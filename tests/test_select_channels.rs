@@ -0,0 +1,65 @@
+use comtrade::ComtradeError;
+
+mod common;
+
+use common::load_comtrade;
+
+#[test]
+fn it_selects_and_reorders_channels() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let selected = record
+        .select_channels(&[3, 0], &[1])
+        .expect("unable to select channels");
+
+    let names: Vec<&str> = selected
+        .analog_channels
+        .iter()
+        .map(|c| c.config.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["3I0", "IA"]);
+    assert_eq!(selected.analog_channels[0].config.index.get(), 1);
+    assert_eq!(selected.analog_channels[1].config.index.get(), 2);
+    assert_eq!(
+        selected.analog_channels[0].data,
+        record.analog_channels[3].data
+    );
+
+    assert_eq!(selected.status_channels.len(), 1);
+    assert_eq!(selected.status_channels[0].config.name, "51B");
+    assert_eq!(selected.status_channels[0].config.index.get(), 1);
+
+    assert_eq!(selected.timestamps, record.timestamps);
+    assert_eq!(selected.sampling_rates, record.sampling_rates);
+}
+
+#[test]
+fn it_removes_channels() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let removed = record
+        .remove_channels(&[3], &[3])
+        .expect("unable to remove channels");
+
+    assert_eq!(removed.analog_channels.len(), 3);
+    assert_eq!(removed.status_channels.len(), 3);
+    assert!(removed.analog_channel_by_name("3I0").is_none());
+    assert!(removed.status_channel_by_name("51N").is_none());
+
+    assert_eq!(
+        record.remove_channels(&[4], &[]),
+        Err(ComtradeError::AnalogChannelNotFound(4))
+    );
+    assert_eq!(
+        record.select_channels(&[], &[7]),
+        Err(ComtradeError::StatusChannelNotFound(7))
+    );
+}
+
+#[test]
+fn it_filters_channels_by_config() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let filtered = record.filter_channels(|a| a.name.starts_with('I'), |s| s.name != "51N");
+
+    assert_eq!(filtered.analog_channels.len(), 3);
+    assert_eq!(filtered.status_channels.len(), 3);
+    assert_eq!(filtered.analog_channels[2].config.index.get(), 3);
+}
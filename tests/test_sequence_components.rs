@@ -0,0 +1,36 @@
+use comtrade::analysis::{PhaseGroup, PhasorOptions};
+
+mod common;
+
+use common::load_comtrade;
+
+#[test]
+fn it_groups_phases_and_calculates_sequence_components() {
+    let mut record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    for (channel, phase) in record.analog_channels.iter_mut().zip(["A", "b", "C", "N"]) {
+        channel.config.phase = phase.to_string();
+    }
+
+    let groups = record.three_phase_groups();
+    assert_eq!(
+        groups,
+        vec![PhaseGroup {
+            circuit_component: "Line123".to_string(),
+            units: "A".to_string(),
+            a: 0,
+            b: 1,
+            c: 2,
+        }]
+    );
+
+    let options = PhasorOptions::default();
+    let sequence = record.sequence_components(&groups[0], &options).unwrap();
+    let residual = record.phasors(3, &options).unwrap();
+    assert_eq!(sequence.timestamps, residual.timestamps);
+
+    // The 3I0 channel should be three times the zero sequence current.
+    for (components, measured) in sequence.values.iter().zip(residual.values.iter()) {
+        let calculated = components.zero * 3.0;
+        assert!((calculated - *measured).magnitude() < 0.05 * measured.magnitude() + 0.5);
+    }
+}
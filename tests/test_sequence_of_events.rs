@@ -0,0 +1,81 @@
+use regex::Regex;
+
+use comtrade::status::StatusTransition;
+
+mod common;
+
+use common::{load_comtrade, synthetic_comtrade};
+
+#[test]
+fn it_lists_status_changes_in_time_order() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let events = record.sequence_of_events();
+
+    let summary: Vec<(&str, usize, u8)> = events
+        .iter()
+        .map(|e| (e.name.as_str(), e.sample, e.to))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![("51N", 10, 1), ("51A", 13, 1), ("51B", 13, 1)]
+    );
+
+    let first = &events[0];
+    assert_eq!(first.channel, 3);
+    assert_eq!(first.time, record.timestamps[10]);
+    assert_eq!(first.absolute_time, record.sample_time(10).unwrap());
+    assert!(
+        (first.time_from_trigger - (record.timestamps[10] - record.trigger_offset())).abs() < 1e-12
+    );
+    assert!(first.time_from_trigger < 0.0);
+    assert!(first.is_rising());
+    assert!(first.abnormal);
+}
+
+#[test]
+fn it_filters_events_by_channel_name() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let names = |events: Vec<StatusTransition>| -> Vec<String> {
+        events.into_iter().map(|e| e.name).collect()
+    };
+    assert_eq!(
+        names(record.sequence_of_events_matching(&Regex::new("^51[AB]$").unwrap())),
+        vec!["51A", "51B"]
+    );
+    assert_eq!(
+        names(record.sequence_of_events_matching_glob("51n")),
+        vec!["51N"]
+    );
+    assert!(record.sequence_of_events_matching_glob("51C").is_empty());
+}
+
+#[test]
+fn it_flags_changes_away_from_normal_status() {
+    let mut record = synthetic_comtrade(
+        1000.0,
+        50.0,
+        vec![],
+        vec![("52a", vec![1, 1, 0, 0, 1]), ("52b", vec![0, 0, 1, 1, 0])],
+    );
+    // Breaker normally closed.
+    record.status_channels[0].config.normal_status_value = 1;
+
+    let events = record.status_transitions(0);
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| (e.sample, e.from, e.to, e.abnormal))
+            .collect::<Vec<_>>(),
+        vec![(2, 1, 0, true), (4, 0, 1, false)]
+    );
+
+    let all: Vec<StatusTransition> = record.sequence_of_events();
+    assert_eq!(all.len(), 4);
+    assert_eq!(
+        all.iter()
+            .map(|e| (e.channel, e.abnormal))
+            .collect::<Vec<_>>(),
+        vec![(0, true), (1, true), (0, false), (1, false)]
+    );
+    assert!(record.status_transitions(5).is_empty());
+}
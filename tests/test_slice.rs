@@ -0,0 +1,88 @@
+use chrono::NaiveDate;
+use float_cmp::approx_eq;
+
+use comtrade::{ComtradeError, SamplingRate};
+
+mod common;
+
+use common::load_comtrade;
+
+#[test]
+fn it_slices_by_sample_range() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let sliced = record.slice(12..24).expect("unable to slice record");
+
+    assert_eq!(sliced.sample_numbers, (1..=12).collect::<Vec<u32>>());
+    assert_eq!(sliced.timestamps.len(), 12);
+    assert!(approx_eq!(f64, sliced.timestamps[0], 0.0));
+    assert!(approx_eq!(
+        f64,
+        sliced.timestamps[11],
+        11.0 / 1200.0,
+        ulps = 4
+    ));
+    assert_eq!(
+        sliced.start_time,
+        NaiveDate::from_ymd(2011, 1, 12).and_hms_micro(5, 55, 30, 760_110)
+    );
+    assert_eq!(sliced.trigger_time, record.trigger_time);
+    assert_eq!(
+        sliced.sampling_rates,
+        vec![SamplingRate {
+            rate_hz: 1200.0,
+            end_sample_number: 12
+        }]
+    );
+    assert_eq!(
+        sliced.analog_channels[0].data,
+        record.analog_channels[0].data[12..24]
+    );
+    assert_eq!(
+        sliced.status_channels[3].data,
+        record.status_channels[3].data[12..24]
+    );
+}
+
+#[test]
+fn it_recalculates_analog_limits_when_slicing() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let sliced = record.slice(0..10).expect("unable to slice record");
+    let ia = &sliced.analog_channels[0];
+
+    // IA peaks at 30.92156982421875 in the first 10 samples and bottoms out at
+    // -9.39605712890625, which are raw values 271 and -83.
+    assert!(approx_eq!(f64, ia.config.max_value, 271.0, epsilon = 1e-9));
+    assert!(approx_eq!(f64, ia.config.min_value, -83.0, epsilon = 1e-9));
+}
+
+#[test]
+fn it_slices_by_time_range() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let trigger = record.trigger_offset();
+    let sliced = record
+        .slice(trigger - 0.005..trigger + 0.005)
+        .expect("unable to slice record");
+
+    assert_eq!(sliced.sample_numbers.len(), 7);
+    assert!(approx_eq!(
+        f64,
+        sliced.trigger_offset(),
+        0.005,
+        epsilon = 1e-6
+    ));
+}
+
+#[test]
+fn it_rejects_invalid_ranges() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+
+    assert_eq!(
+        record.slice(30..50),
+        Err(ComtradeError::SampleRangeOutOfBounds {
+            start: 30,
+            end: 50,
+            len: 40
+        })
+    );
+    assert_eq!(record.slice(5.0..6.0), Err(ComtradeError::EmptyRange));
+}
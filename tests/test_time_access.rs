@@ -0,0 +1,73 @@
+use chrono::NaiveDate;
+use float_cmp::approx_eq;
+
+use comtrade::Interpolation;
+
+mod common;
+
+use common::load_comtrade;
+
+#[test]
+fn it_calculates_trigger_offset_and_sample_times() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+
+    assert!(approx_eq!(
+        f64,
+        record.trigger_offset(),
+        0.0325,
+        epsilon = 1e-9
+    ));
+    assert_eq!(
+        record.sample_time(12),
+        Some(NaiveDate::from_ymd(2011, 1, 12).and_hms_micro(5, 55, 30, 760_110))
+    );
+    assert_eq!(record.sample_index_at_time(0.0104), Some(12));
+    assert_eq!(record.sample_index_at_time(-1.0), Some(0));
+    assert_eq!(record.sample_index_at_time(1.0), Some(39));
+}
+
+#[test]
+fn it_interpolates_analog_values() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+    let ia = &record.analog_channels[0].data;
+
+    let halfway = 0.5 / 1200.0;
+    let nearest = record
+        .value_at_time(0, halfway * 0.9, Interpolation::Nearest)
+        .unwrap();
+    assert_eq!(nearest, ia[0]);
+
+    let linear = record
+        .value_at_time(0, halfway, Interpolation::Linear)
+        .unwrap();
+    assert!(approx_eq!(f64, linear, (ia[0] + ia[1]) / 2.0, ulps = 4));
+
+    assert!(record
+        .value_at_time(0, 1.0, Interpolation::Linear)
+        .is_none());
+    assert!(record
+        .value_at_time(10, 0.0, Interpolation::Linear)
+        .is_none());
+    assert_eq!(record.status_at_time(0, halfway), Some(0));
+}
+
+#[test]
+fn it_windows_around_the_trigger() {
+    let record = load_comtrade("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+
+    let window = record.window(0.005, 0.01);
+    assert_eq!(window.samples(), 6..13);
+    assert_eq!(window.sample_numbers(), &record.sample_numbers[6..13]);
+    assert_eq!(
+        window.analog(1).unwrap(),
+        &record.analog_channels[1].data[6..13]
+    );
+    assert_eq!(window.analog_channels().count(), 4);
+
+    let pre_fault = record.pre_fault(0.01);
+    assert_eq!(pre_fault.samples(), 27..39);
+    assert!(pre_fault.end().unwrap() < record.trigger_offset());
+
+    let post_fault = record.post_fault(0.1);
+    assert_eq!(post_fault.samples(), 39..40);
+}
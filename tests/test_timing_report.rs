@@ -0,0 +1,110 @@
+use chrono::Duration;
+
+use comtrade::status::{StatusRoles, TimingOptions};
+use comtrade::Comtrade;
+
+mod common;
+
+use common::{stepped_sine_wave, synthetic_comtrade};
+
+const RATE: f64 = 2400.0;
+const NUM_SAMPLES: usize = 600;
+
+/// Status channel which changes from `initial` to the other value at `sample`.
+fn switched(initial: u8, sample: usize) -> Vec<u8> {
+    (0..NUM_SAMPLES)
+        .map(|n| if n < sample { initial } else { 1 - initial })
+        .collect()
+}
+
+/// Fault at 100ms, picked up at 105ms, tripped at 120ms, current interrupted at
+/// sample 400 (~166.7ms) and the breaker auxiliary contacts changing after that.
+fn cleared_fault() -> Comtrade {
+    let current = |angle: f64| {
+        stepped_sine_wave(
+            RATE,
+            NUM_SAMPLES,
+            60.0,
+            &[
+                (0, 100.0, angle),
+                (240, 2000.0, angle - 60.0),
+                (400, 0.0, 0.0),
+            ],
+        )
+    };
+    let mut record = synthetic_comtrade(
+        RATE,
+        60.0,
+        vec![
+            ("IA", "A", "Line1", "A", current(0.0)),
+            ("IB", "B", "Line1", "A", current(-120.0)),
+            ("IC", "C", "Line1", "A", current(120.0)),
+        ],
+        vec![
+            ("Ph IOC Pickup", switched(0, 252)),
+            ("Trip Out 1", switched(0, 288)),
+            ("52a", switched(1, 408)),
+            ("52b", switched(0, 413)),
+        ],
+    );
+    record.status_channels[2].config.normal_status_value = 1;
+    record.trigger_time = record.start_time + Duration::milliseconds(100);
+    record
+}
+
+fn options() -> TimingOptions {
+    TimingOptions {
+        roles: StatusRoles {
+            pickup: vec!["* Pickup".to_string()],
+            trip: vec!["Trip*".to_string()],
+            breaker_a: vec!["52a".to_string()],
+            breaker_b: vec!["52b".to_string()],
+        },
+        currents: vec![0, 1, 2],
+        interruption_threshold: 50.0,
+        fault_inception: None,
+    }
+}
+
+#[test]
+fn it_times_protection_and_breaker_operation() {
+    let record = cleared_fault();
+    let report = record.timing_report(&options()).unwrap();
+
+    let at = |sample: usize| record.timestamps[sample] - 0.1;
+    assert!((report.fault_inception - 0.1).abs() < 1e-9);
+    assert!((report.pickup.unwrap() - at(252)).abs() < 1e-9);
+    assert!((report.trip.unwrap() - at(288)).abs() < 1e-9);
+    assert!((report.breaker_open.unwrap() - at(408)).abs() < 1e-9);
+
+    let interruption = report.current_interruption.unwrap();
+    assert!((interruption - at(400)).abs() < 0.001, "{}", interruption);
+
+    assert!((report.pickup_to_trip().unwrap() - 0.015).abs() < 1e-9);
+    assert!((report.trip_to_breaker_open().unwrap() - (at(408) - at(288))).abs() < 1e-9);
+    assert!(report.trip_to_interruption().unwrap() > 0.04);
+}
+
+#[test]
+fn it_reports_missing_stages() {
+    let record = cleared_fault();
+    let options = TimingOptions {
+        roles: StatusRoles {
+            trip: vec!["Trip*".to_string()],
+            ..StatusRoles::default()
+        },
+        interruption_threshold: 5000.0,
+        fault_inception: Some(0.09),
+        ..options()
+    };
+    let report = record.timing_report(&options).unwrap();
+
+    assert_eq!(report.fault_inception, 0.09);
+    assert_eq!(report.pickup, None);
+    assert_eq!(report.pickup_to_trip(), None);
+    assert!((report.trip.unwrap() - (record.timestamps[288] - 0.09)).abs() < 1e-9);
+    assert_eq!(report.breaker_open, None);
+
+    // Never above the threshold, so counts as interrupted as soon as it trips.
+    assert_eq!(report.trip_to_interruption(), Some(0.0));
+}
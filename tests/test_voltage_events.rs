@@ -0,0 +1,136 @@
+use comtrade::analysis::{PhaseGroup, SagSwellOptions, SagType, VoltageEventKind};
+use comtrade::Comtrade;
+
+mod common;
+
+use common::{stepped_sine_wave, synthetic_comtrade};
+
+const RATE: f64 = 2400.0;
+const NUM_SAMPLES: usize = 1200;
+const NOMINAL: f64 = 63.5;
+
+/// Half second record of phase to neutral voltages in kV which change to the
+/// given per unit magnitudes and angles (in degrees) between the given samples.
+fn record(event: [(f64, f64); 3], start: usize, end: usize) -> Comtrade {
+    let phase = |p: usize, angle: f64| {
+        stepped_sine_wave(
+            RATE,
+            NUM_SAMPLES,
+            60.0,
+            &[
+                (0, NOMINAL, angle),
+                (start, NOMINAL * event[p].0, event[p].1),
+                (end, NOMINAL, angle),
+            ],
+        )
+    };
+    synthetic_comtrade(
+        RATE,
+        60.0,
+        vec![
+            ("VA", "A", "Bus1", "kV", phase(0, 0.0)),
+            ("VB", "B", "Bus1", "kV", phase(1, -120.0)),
+            ("VC", "C", "Bus1", "kV", phase(2, 120.0)),
+        ],
+        vec![],
+    )
+}
+
+fn group() -> PhaseGroup {
+    PhaseGroup {
+        circuit_component: "Bus1".to_string(),
+        units: "kV".to_string(),
+        a: 0,
+        b: 1,
+        c: 2,
+    }
+}
+
+#[test]
+fn it_characterises_a_single_phase_sag() {
+    // Phase A drops to 0.5 per unit from 200ms, at its peak, for 100ms.
+    let record = record([(0.5, 0.0), (1.0, -120.0), (1.0, 120.0)], 480, 720);
+    let events = record
+        .voltage_events(&group(), &SagSwellOptions::default())
+        .unwrap();
+
+    assert_eq!(events.len(), 1);
+    let sag = &events[0];
+    assert_eq!(sag.kind, VoltageEventKind::Sag);
+    assert_eq!(sag.phases, [true, false, false]);
+    assert_eq!(sag.sag_type, Some(SagType::B));
+    assert!((sag.magnitude() - 0.5).abs() < 0.01);
+    assert!((sag.depth() - 0.5).abs() < 0.01);
+    assert!((sag.phase_magnitudes[1] - 1.0).abs() < 0.01);
+
+    // The half cycle RMS lags the waveform but its duration is about right.
+    assert!(sag.start > 0.2 && sag.start < 0.2 + 1.0 / 60.0);
+    assert!((sag.duration() - 0.1).abs() < 1.0 / 60.0);
+
+    assert!((sag.inception - 0.2).abs() < 1.0 / RATE);
+    assert!((sag.recovery - 0.3).abs() < 1.0 / RATE);
+    assert!((sag.inception_point_on_wave - 90.0).abs() < 10.0);
+    assert!((sag.recovery_point_on_wave - 90.0).abs() < 10.0);
+}
+
+#[test]
+fn it_finds_point_on_wave_at_zero_crossings() {
+    // A quarter cycle later, phase A is crossing zero going negative.
+    let record = record([(0.3, 0.0), (1.0, -120.0), (1.0, 120.0)], 490, 730);
+    let events = record
+        .voltage_events(&group(), &SagSwellOptions::default())
+        .unwrap();
+
+    assert_eq!(events.len(), 1);
+    assert!((events[0].inception_point_on_wave - 180.0).abs() < 10.0);
+}
+
+#[test]
+fn it_classifies_phase_to_phase_sags() {
+    // Phases B and C pulled together by a phase to phase fault.
+    let magnitude = (0.25f64 + 0.75 * 0.25).sqrt();
+    let angle = (0.25 * 3f64.sqrt() / 0.5).atan().to_degrees();
+    let record = record(
+        [
+            (1.0, 0.0),
+            (magnitude, -180.0 + angle),
+            (magnitude, 180.0 - angle),
+        ],
+        480,
+        720,
+    );
+    let events = record
+        .voltage_events(&group(), &SagSwellOptions::default())
+        .unwrap();
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].phases, [false, true, true]);
+    assert_eq!(events[0].sag_type, Some(SagType::C));
+}
+
+#[test]
+fn it_finds_swells_against_a_declared_reference() {
+    let record = record([(1.2, 0.0), (1.2, -120.0), (1.2, 120.0)], 480, 720);
+    let options = SagSwellOptions {
+        reference: Some(NOMINAL),
+        ..Default::default()
+    };
+    let events = record.voltage_events(&group(), &options).unwrap();
+
+    assert_eq!(events.len(), 1);
+    let swell = &events[0];
+    assert_eq!(swell.kind, VoltageEventKind::Swell);
+    assert_eq!(swell.phases, [true, true, true]);
+    assert_eq!(swell.sag_type, None);
+    assert!((swell.magnitude() - 1.2).abs() < 0.01);
+
+    // Nothing to find against a reference it never departs from far enough.
+    let options = SagSwellOptions {
+        reference: Some(NOMINAL * 1.1),
+        ..Default::default()
+    };
+    assert!(record
+        .voltage_events(&group(), &options)
+        .unwrap()
+        .is_empty());
+}